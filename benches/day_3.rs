@@ -0,0 +1,58 @@
+use aoc_2023::day_3;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+/// A random engine schematic of the given size, with roughly the same mix of
+/// numbers, symbols and gaps as the real inputs
+fn schematic(size: usize) -> String {
+    let mut rng = SmallRng::seed_from_u64(3);
+    let mut out = String::with_capacity(size * (size + 1));
+    for _ in 0..size {
+        let mut row = String::with_capacity(size);
+        while row.len() < size {
+            match rng.gen_range(0..10) {
+                0..=1 => {
+                    let digits = rng.gen_range(1..=3).min(size - row.len());
+                    for _ in 0..digits {
+                        row.push(char::from(b'0' + rng.gen_range(0..10)));
+                    }
+                    if row.len() < size {
+                        row.push('.');
+                    }
+                }
+                2 => row.push(['*', '#', '+', '$', '/', '='][rng.gen_range(0..6)]),
+                _ => row.push('.'),
+            }
+        }
+        out.push_str(&row);
+        out.push('\n');
+    }
+    out
+}
+
+fn bench_day_3(c: &mut Criterion) {
+    for size in [140, 1000] {
+        let input = day_3::parse(&schematic(size));
+        assert_eq!(
+            day_3::solve_part_1(&input),
+            day_3::solve_part_1_naive(&input)
+        );
+
+        let mut group = c.benchmark_group("day_3");
+        group.bench_with_input(
+            BenchmarkId::new("part_1_naive", size),
+            &input,
+            |b, input| b.iter(|| day_3::solve_part_1_naive(black_box(input))),
+        );
+        group.bench_with_input(BenchmarkId::new("part_1", size), &input, |b, input| {
+            b.iter(|| day_3::solve_part_1(black_box(input)))
+        });
+        group.bench_with_input(BenchmarkId::new("part_2", size), &input, |b, input| {
+            b.iter(|| day_3::solve_part_2(black_box(input)))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_day_3);
+criterion_main!(benches);
@@ -6,29 +6,61 @@ pub fn parse(input: &str) -> Vec<String> {
         .collect()
 }
 
+/// The two digit calibration value of each line, made from its first and last
+/// digit characters
+pub fn calibration_values_part1(input: &[String]) -> Vec<u32> {
+    input
+        .iter()
+        .map(|line| {
+            let mut digits = line
+                .chars()
+                .filter(|c| c.is_digit(10))
+                .map(|c| c.to_digit(10).unwrap());
+
+            let first = digits.next().unwrap();
+            let last = digits.last().unwrap_or(first);
+            first * 10 + last
+        })
+        .collect()
+}
+
 pub fn solve_part_1(input: &[String]) -> u32 {
-    let mut sum = 0;
-    for line in input {
-        // First and last digit character of the string
-        let mut digits = line
-            .chars()
-            .filter(|c| c.is_digit(10))
-            .map(|c| c.to_digit(10).unwrap());
-
-        let first = digits.next().unwrap();
-        let last = digits.last().unwrap_or(first);
-        let num = first * 10 + last;
-        sum += num;
-    }
-    sum
+    calibration_values_part1(input).iter().sum()
 }
 
 const DIGIT_STRS: [&str; 9] = [
     "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
 ];
 
-struct Part2Digits<'a> {
+/// Iterates the digits in a string, whether written as digit characters or
+/// spelled out as words.
+///
+/// Words may overlap, eg "eightwo" yields both 8 and 2.
+pub struct Part2Digits<'a> {
     source: &'a str,
+
+    /// The spelled out digits, where `words[i]` spells out the digit `i + 1`
+    words: &'a [&'a str],
+}
+
+impl<'a> Part2Digits<'a> {
+    /// Spelling out digits with the English words
+    pub fn new(source: &'a str) -> Self {
+        Self::with_words(source, &DIGIT_STRS)
+    }
+
+    /// Spelling out digits with `words`, where `words[i]` spells out the
+    /// digit `i + 1`
+    pub fn with_words(source: &'a str, words: &'a [&'a str]) -> Self {
+        Self { source, words }
+    }
+
+    /// Drop the first character of the source
+    fn advance(&mut self) {
+        let mut chars = self.source.chars();
+        chars.next();
+        self.source = chars.as_str();
+    }
 }
 
 impl<'a> Iterator for Part2Digits<'a> {
@@ -38,39 +70,70 @@ impl<'a> Iterator for Part2Digits<'a> {
         while !self.source.is_empty() {
             // If the first char is a digit, return it
             if let Some(digit) = self.source.chars().next().and_then(|c| c.to_digit(10)) {
-                self.source = &self.source[1..];
+                self.advance();
                 return Some(digit);
             }
 
             // Could do something fancy based around common prefixes here, but
             // it is probably fast enough to just brute-force search through all
             // of the possible digit strings instead
-            for (digit_idx, digit_str) in DIGIT_STRS.iter().enumerate() {
+            for (digit_idx, digit_str) in self.words.iter().enumerate() {
                 if self.source.starts_with(digit_str) {
-                    self.source = &self.source[1..];
+                    // Only skip the first character, as the next word may
+                    // overlap with this one
+                    self.advance();
                     return Some(digit_idx as u32 + 1);
                 }
             }
 
             // If we get here, we didn't find a digit or digit string, so skip
             // the first character and try again
-            self.source = &self.source[1..];
+            self.advance();
         }
 
         None
     }
 }
 
-pub fn solve_part_2(input: &[String]) -> u32 {
-    let mut sum = 0;
-    for line in input {
-        let mut digits = Part2Digits { source: line };
-        let first = digits.next().unwrap();
-        let last = digits.last().unwrap_or(first);
-        let num = first * 10 + last;
-        sum += num;
+/// The last digit in the line, either as a digit character or as one of
+/// `words`, where `words[i]` spells out the digit `i + 1`.
+///
+/// Scans backwards from the end of the line, so stops at the first match
+/// rather than walking through every digit in the line.
+fn last_digit(line: &str, words: &[&str]) -> Option<u32> {
+    let mut source = line;
+    while let Some(c) = source.chars().next_back() {
+        if let Some(digit) = c.to_digit(10) {
+            return Some(digit);
+        }
+
+        for (digit_idx, word) in words.iter().enumerate() {
+            if source.ends_with(word) {
+                return Some(digit_idx as u32 + 1);
+            }
+        }
+
+        source = &source[..source.len() - c.len_utf8()];
     }
-    sum
+
+    None
+}
+
+/// The two digit calibration value of each line, made from its first and last
+/// digits, including those spelled out as words
+pub fn calibration_values_part2(input: &[String]) -> Vec<u32> {
+    input
+        .iter()
+        .map(|line| {
+            let first = Part2Digits::new(line).next().unwrap();
+            let last = last_digit(line, &DIGIT_STRS).unwrap();
+            first * 10 + last
+        })
+        .collect()
+}
+
+pub fn solve_part_2(input: &[String]) -> u32 {
+    calibration_values_part2(input).iter().sum()
 }
 
 #[cfg(test)]
@@ -89,24 +152,70 @@ treb7uchet",
         assert_eq!(solve_part_1(&input), 142);
     }
 
+    #[test]
+    fn test_calibration_values_part1() {
+        let input = parse(
+            "1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet",
+        );
+
+        assert_eq!(calibration_values_part1(&input), vec![12, 38, 15, 77]);
+    }
+
     #[test]
     fn test_part_2_digits() {
         let line = "fivetwoqmlk22eightfive";
         assert_eq!(
             vec![5, 2, 2, 2, 8, 5],
-            Part2Digits { source: line }.collect::<Vec<_>>()
+            Part2Digits::new(line).collect::<Vec<_>>()
         );
 
         // "zero" isn't a digit in this problem
         let line = "zeroonetwo012";
         assert_eq!(
             vec![1, 2, 0, 1, 2],
-            Part2Digits { source: line }.collect::<Vec<_>>()
+            Part2Digits::new(line).collect::<Vec<_>>()
         );
 
         // Stupid overlapping words
         let line = "eightwo";
-        assert_eq!(vec![8, 2], Part2Digits { source: line }.collect::<Vec<_>>());
+        assert_eq!(vec![8, 2], Part2Digits::new(line).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_part_2_digits_with_words() {
+        let german = [
+            "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+        ];
+        let digits = |line| Part2Digits::with_words(line, &german).collect::<Vec<_>>();
+
+        assert_eq!(digits("fünfxzwei7acht"), vec![5, 2, 7, 8]);
+        assert_eq!(digits("zweins"), vec![2, 1]);
+        assert_eq!(digits("oneightfünf"), vec![5]);
+
+        // The English words are the default
+        let english = Part2Digits::with_words("eightwo", &DIGIT_STRS);
+        assert_eq!(english.collect::<Vec<_>>(), vec![8, 2]);
+
+        // Only the first few digits have words
+        let partial = Part2Digits::with_words("onetwothree", &["one", "two"]);
+        assert_eq!(partial.collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_last_digit() {
+        assert_eq!(last_digit("xtwone3four", &DIGIT_STRS), Some(4));
+        assert_eq!(last_digit("4nineeightseven2", &DIGIT_STRS), Some(2));
+
+        // Ending in a word, including words overlapping their neighbours
+        assert_eq!(last_digit("abcone2threexyz", &DIGIT_STRS), Some(3));
+        assert_eq!(last_digit("eightwo", &DIGIT_STRS), Some(2));
+        assert_eq!(last_digit("twone", &DIGIT_STRS), Some(1));
+
+        assert_eq!(last_digit("abc", &DIGIT_STRS), None);
+        assert_eq!(last_digit("seven", &[]), None);
     }
 
     #[test]
@@ -123,4 +232,22 @@ zoneight234
 
         assert_eq!(solve_part_2(&input), 281);
     }
+
+    #[test]
+    fn test_calibration_values_part2() {
+        let input = parse(
+            "two1nine
+eightwothree
+abcone2threexyz
+xtwone3four
+4nineeightseven2
+zoneight234
+7pqrstsixteen",
+        );
+
+        assert_eq!(
+            calibration_values_part2(&input),
+            vec![29, 83, 13, 24, 42, 14, 76]
+        );
+    }
 }
@@ -102,7 +102,7 @@ pub fn parse(input: &str) -> Input {
     assert_eq!(candidate_connections.len(), 2);
 
     let source_cell = Cell::from_dir_pair(candidate_connections[0], candidate_connections[1]);
-    *map.get_mut(source).unwrap() = source_cell;
+    map[source] = source_cell;
 
     Input { map, source }
 }
@@ -142,7 +142,7 @@ pub fn solve_part_2(input: &Input) -> u64 {
     let mut loop_map = Map2d::new_default(input.map.size, Cell::Empty);
 
     for pos in iter_pipe_loop(input) {
-        *loop_map.get_mut(pos).unwrap() = input.map.get(pos).unwrap();
+        loop_map[pos] = input.map[pos];
     }
 
     // Now count up in scanlines
@@ -161,3 +161,36 @@ pub fn solve_part_2(input: &Input) -> u64 {
 
     count
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_1() {
+        let input = parse(
+            ".....
+.S-7.
+.|.|.
+.L-J.
+.....",
+        );
+        assert_eq!(solve_part_1(&input), 4);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = parse(
+            "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........",
+        );
+        assert_eq!(solve_part_2(&input), 4);
+    }
+}
@@ -1,4 +1,7 @@
 use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::util::Memoizer;
 
 pub struct Row {
     cells: Vec<u8>,
@@ -56,9 +59,64 @@ fn can_fit(cells: &[u8], pos: usize, block: usize) -> bool {
 }
 
 
-// Since we never mutate cells/blocks, only trimming elements from the front, we
-// can memoize the results on the lengths of the slices rather than their contents.
-fn count_ways_to_fit(cells: &[u8], blocks: &[usize], memo: &mut HashMap<(usize, usize), u64>) -> u64 {
+/// Results of [`count_ways_from`], keyed on the contents of the remaining
+/// cells and blocks rather than their offsets so that one memo can be shared
+/// between rows.
+///
+/// Rather than copying those contents on every lookup, each distinct suffix is
+/// interned once per row and the results keyed on the interned IDs.
+#[derive(Default)]
+struct Memo {
+    cell_suffixes: HashMap<Vec<u8>, usize>,
+    block_suffixes: HashMap<Vec<usize>, usize>,
+    counts: Memoizer<(usize, usize), u64>,
+}
+
+impl Memo {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of distinct pairs of suffixes with a cached result
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.counts.len()
+    }
+}
+
+/// The interned ID of every suffix of `items`, indexed by where it starts
+fn intern_suffixes<T: Clone + Hash + Eq>(
+    items: &[T],
+    ids: &mut HashMap<Vec<T>, usize>,
+) -> Vec<usize> {
+    (0..=items.len())
+        .map(|start| {
+            let suffix = &items[start..];
+            if let Some(&id) = ids.get(suffix) {
+                return id;
+            }
+
+            let id = ids.len();
+            ids.insert(suffix.to_vec(), id);
+            id
+        })
+        .collect()
+}
+
+/// The number of ways to fit `row.blocks[block_start..]` into
+/// `row.cells[cell_start..]`, where `cell_ids` and `block_ids` are the interned
+/// IDs of the row's suffixes
+fn count_ways_from(
+    row: &Row,
+    cell_ids: &[usize],
+    block_ids: &[usize],
+    cell_start: usize,
+    block_start: usize,
+    counts: &mut Memoizer<(usize, usize), u64>,
+) -> u64 {
+    let cells = &row.cells[cell_start..];
+    let blocks = &row.blocks[block_start..];
+
     if blocks.is_empty() {
         if cells.iter().all(|c| *c != b'#') {
             return 1;
@@ -67,58 +125,138 @@ fn count_ways_to_fit(cells: &[u8], blocks: &[usize], memo: &mut HashMap<(usize,
         }
     }
 
-    if let Some(memo) = memo.get(&(cells.len(), blocks.len())) {
-        return *memo;
-    }
+    let key = (cell_ids[cell_start], block_ids[block_start]);
+    counts.get_or_compute(key, |counts| {
+        let slack = match cells
+            .len()
+            .checked_sub(blocks.iter().sum::<usize>())
+            .and_then(|x| x.checked_sub(blocks.len() - 1))
+        {
+            Some(slack) => slack,
+            None => return 0,
+        };
 
-    let slack = match cells
-        .len()
-        .checked_sub(blocks.iter().sum::<usize>())
-        .and_then(|x| x.checked_sub(blocks.len() - 1))
-    {
-        Some(slack) => slack,
-        None => return 0,
-    };
+        let mut sum = 0;
+        for pos in 0..=slack {
+            if can_fit(cells, pos as usize, blocks[0]) {
+                let cut = std::cmp::min(cells.len(), pos + blocks[0] + 1);
+                sum += count_ways_from(
+                    row,
+                    cell_ids,
+                    block_ids,
+                    cell_start + cut,
+                    block_start + 1,
+                    counts,
+                );
+            }
+        }
 
-    let mut sum = 0;
-    for pos in 0..=slack {
-        if can_fit(cells, pos as usize, blocks[0]) {
-            let cut = std::cmp::min(cells.len(), pos + blocks[0] + 1);
-            let remaining = &cells[cut..];
-            sum += count_ways_to_fit(remaining, &blocks[1..], memo);
+        sum
+    })
+}
+
+fn count_ways_to_fit(row: &Row, memo: &mut Memo) -> u64 {
+    let cell_ids = intern_suffixes(&row.cells, &mut memo.cell_suffixes);
+    let block_ids = intern_suffixes(&row.blocks, &mut memo.block_suffixes);
+    count_ways_from(row, &cell_ids, &block_ids, 0, 0, &mut memo.counts)
+}
+
+/// Every way of filling in the unknown cells of `row` that is consistent with
+/// its blocks. Only practical for small rows, but handy for checking counts.
+pub fn arrangements(row: &Row) -> Vec<Vec<u8>> {
+    fn fill_from(
+        row: &Row,
+        cell_start: usize,
+        block_start: usize,
+        filled: &mut Vec<u8>,
+        out: &mut Vec<Vec<u8>>,
+    ) {
+        let cells = &row.cells[cell_start..];
+        let blocks = &row.blocks[block_start..];
+
+        if blocks.is_empty() {
+            if cells.iter().all(|c| *c != b'#') {
+                let mut arrangement = filled.clone();
+                arrangement.resize(row.cells.len(), b'.');
+                out.push(arrangement);
+            }
+            return;
+        }
+
+        for pos in 0..cells.len() {
+            if can_fit(cells, pos, blocks[0]) {
+                let cut = std::cmp::min(cells.len(), pos + blocks[0] + 1);
+                let len = filled.len();
+                filled.resize(len + pos, b'.');
+                filled.resize(len + pos + blocks[0], b'#');
+                filled.resize(len + cut, b'.');
+                fill_from(row, cell_start + cut, block_start + 1, filled, out);
+                filled.truncate(len);
+            }
         }
     }
 
-    memo.insert((cells.len(), blocks.len()), sum);
-    sum
+    let mut out = Vec::new();
+    fill_from(row, 0, 0, &mut Vec::new(), &mut out);
+    out
 }
 
 pub fn solve_part_1(input: &[Row]) -> u64 {
+    let mut memo = Memo::new();
     input
         .iter()
-        .map(|row| count_ways_to_fit(&row.cells, &row.blocks, &mut HashMap::new()))
+        .map(|row| count_ways_to_fit(row, &mut memo))
         .sum()
 }
 
-pub fn solve_part_2(input: &[Row]) -> u64 {
+/// The row with its cells and blocks repeated five times, with an unknown cell
+/// between each copy of the cells
+fn unfold(row: &Row) -> Row {
+    let mut cells = Vec::new();
+    for _ in 0..5 {
+        cells.extend_from_slice(&row.cells);
+        cells.push(b'?');
+    }
+    cells.pop();
+
+    let blocks = row.blocks.repeat(5);
+
+    Row { cells, blocks }
+}
+
+#[cfg(any(test, not(feature = "rayon")))]
+fn solve_part_2_serial(input: &[Row]) -> u64 {
+    let mut memo = Memo::new();
     input
         .iter()
-        .map(|row| {
-            let mut cells = Vec::new();
-            for _ in 0..5 {
-                cells.extend_from_slice(&row.cells);
-                cells.push(b'?');
-            }
-            cells.pop();
+        .map(|row| count_ways_to_fit(&unfold(row), &mut memo))
+        .sum()
+}
 
-            let blocks = row.blocks.repeat(5);
+/// As [`solve_part_2`], counting the rows in parallel with a memo per row
+#[cfg(feature = "rayon")]
+pub fn solve_part_2_parallel(input: &[Row]) -> u64 {
+    use rayon::prelude::*;
 
-            Row { cells, blocks}
-        })
-        .map(|row| count_ways_to_fit(&row.cells, &row.blocks, &mut HashMap::new()))
+    input
+        .par_iter()
+        .map(|row| count_ways_to_fit(&unfold(row), &mut Memo::new()))
         .sum()
 }
 
+/// Counts the rows serially, unless the `rayon` feature is enabled
+pub fn solve_part_2(input: &[Row]) -> u64 {
+    #[cfg(feature = "rayon")]
+    {
+        solve_part_2_parallel(input)
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        solve_part_2_serial(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -141,4 +279,70 @@ mod tests {
         let input = parse(EXAMPLE_INPUT);
         assert_eq!(solve_part_2(&input), 525152);
     }
+
+    #[test]
+    fn test_rows_of_same_shape() {
+        // Same number of cells and blocks, so a memo keyed only on the lengths
+        // of what's left would confuse the two
+        let rows = parse("???.### 1,1,3\n.?????? 1,1,3");
+        let mut memo = Memo::new();
+        assert_eq!(count_ways_to_fit(&rows[0], &mut memo), 1);
+        assert_eq!(count_ways_to_fit(&rows[1], &mut memo), 0);
+        assert_eq!(solve_part_1(&rows), 1);
+    }
+
+    #[test]
+    fn test_shared_memo() {
+        // Once the first block of each row is placed, the rest of the two rows
+        // are identical
+        let a = parse("#.??.### 1,1,3").remove(0);
+        let b = parse("??.??.### 2,1,3").remove(0);
+
+        let mut alone = Memo::new();
+        assert_eq!(count_ways_to_fit(&b, &mut alone), 2);
+
+        let mut shared = Memo::new();
+        assert_eq!(count_ways_to_fit(&a, &mut shared), 2);
+        let after_a = shared.len();
+        assert_eq!(count_ways_to_fit(&b, &mut shared), 2);
+
+        // Row b found its common suffix already in the shared memo
+        assert!(shared.len() - after_a < alone.len());
+    }
+
+    #[test]
+    fn test_arrangements() {
+        let rows = parse(EXAMPLE_INPUT);
+        for row in &rows {
+            let arrangements = arrangements(row);
+            assert_eq!(
+                arrangements.len() as u64,
+                count_ways_to_fit(row, &mut Memo::new())
+            );
+
+            for arrangement in &arrangements {
+                // Only the unknown cells are filled in
+                for (&filled, &cell) in arrangement.iter().zip(&row.cells) {
+                    assert!(cell == b'?' || cell == filled);
+                }
+
+                let blocks = arrangement
+                    .split(|&c| c == b'.')
+                    .filter(|block| !block.is_empty())
+                    .map(|block| block.len())
+                    .collect::<Vec<_>>();
+                assert_eq!(blocks, row.blocks);
+            }
+        }
+
+        let row = parse("???.### 1,1,3").remove(0);
+        assert_eq!(arrangements(&row), vec![b"#.#.###".to_vec()]);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_part_2_parallel() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_2_parallel(&input), solve_part_2_serial(&input));
+    }
 }
@@ -61,7 +61,8 @@ fn slide_up(map: &mut impl Map2dExt<Cell>) {
     }
 }
 
-fn slide(map: &mut Map2d<Cell>, dir: Dir) {
+/// Tilt the map so that every mobile rock rolls as far as it can in `dir`
+pub fn slide_rocks(map: &mut Map2d<Cell>, dir: Dir) {
     let mut rotated = match dir {
         Dir::Up => RotatedMap2d { map, up: Dir::Up },
         Dir::Down => RotatedMap2d { map, up: Dir::Down },
@@ -75,16 +76,21 @@ fn slide(map: &mut Map2d<Cell>, dir: Dir) {
     slide_up(&mut rotated);
 }
 
+/// A single spin cycle, tilting the map up, left, down, then right
+pub fn spin_cycle(map: &mut Map2d<Cell>) {
+    slide_rocks(map, Dir::Up);
+    slide_rocks(map, Dir::Left);
+    slide_rocks(map, Dir::Down);
+    slide_rocks(map, Dir::Right);
+}
+
 fn load(map: &Map2d<Cell>) -> i64 {
     let mut load = 0;
 
-    for (i, cell) in map.data.iter().enumerate() {
-        if *cell != Cell::Mobile {
-            continue;
+    for (pos, cell) in map.iter() {
+        if *cell == Cell::Mobile {
+            load += map.size.y - pos.y
         }
-
-        let pos = map.pos_of(i);
-        load += map.size.y - pos.y
     }
 
     load
@@ -92,7 +98,7 @@ fn load(map: &Map2d<Cell>) -> i64 {
 
 pub fn solve_part_1(input: &Map2d<Cell>) -> i64 {
     let mut map = input.clone();
-    slide(&mut map, Dir::Up);
+    slide_rocks(&mut map, Dir::Up);
     load(&map)
 }
 
@@ -115,13 +121,6 @@ impl From<&Map2d<Cell>> for CacheKey {
 pub fn solve_part_2(input: &Map2d<Cell>) -> i64 {
     let mut map = input.clone();
 
-    let cycle = |map: &mut Map2d<Cell>| {
-        slide(map, Dir::Up);
-        slide(map, Dir::Left);
-        slide(map, Dir::Down);
-        slide(map, Dir::Right);
-    };
-
     // Maps map state -> the first cycle number that state was seen
     let mut seen = HashMap::<CacheKey, usize>::new();
 
@@ -133,7 +132,7 @@ pub fn solve_part_2(input: &Map2d<Cell>) -> i64 {
             second_seen = i;
             break;
         }
-        cycle(&mut map);
+        spin_cycle(&mut map);
     }
 
     let preamble = first_seen;
@@ -142,8 +141,64 @@ pub fn solve_part_2(input: &Map2d<Cell>) -> i64 {
     // The map is currently still at the repeated point of the cycle
     let remaining = (1_000_000_000 - preamble) % period;
     for _ in 0..remaining {
-        cycle(&mut map);
+        spin_cycle(&mut map);
     }
 
     load(&map)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
+
+    #[test]
+    fn test_part_1() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_1(&input), 136);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_2(&input), 64);
+    }
+
+    #[test]
+    fn test_slide_rocks_up() {
+        let mut map = parse(EXAMPLE_INPUT);
+        slide_rocks(&mut map, Dir::Up);
+        assert_eq!(load(&map), solve_part_1(&parse(EXAMPLE_INPUT)));
+        assert_eq!(load(&map), 136);
+    }
+
+    #[test]
+    fn test_spin_cycle() {
+        let mut map = parse(EXAMPLE_INPUT);
+        spin_cycle(&mut map);
+
+        let expected = parse(
+            ".....#....
+....#...O#
+...OO##...
+.OO#......
+.....OOO#.
+.O#...O#.#
+....O#....
+......OOOO
+#...O###..
+#..OO#....",
+        );
+        assert_eq!(map, expected);
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use crate::util::{Dir, Map2d, Map2dExt, Vec2};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -122,7 +124,20 @@ pub fn parse(input: &str) -> Map2d<Tile> {
     Map2d::parse_grid(input, Tile::from_char)
 }
 
-fn count_energized(map: &Map2d<Tile>, source_pos: Vec2, source_dir: Dir) -> usize {
+/// The number of tiles energized by a beam entering the map at `source_pos`,
+/// travelling in `source_dir`
+pub fn energized_count(map: &Map2d<Tile>, source_pos: Vec2, source_dir: Dir) -> usize {
+    energized_with_override(map, source_pos, source_dir, &HashMap::new())
+}
+
+/// As [`energized_count`], but with the tiles at the positions in `overrides`
+/// replaced, without having to clone and modify the whole map
+pub fn energized_with_override(
+    map: &Map2d<Tile>,
+    source_pos: Vec2,
+    source_dir: Dir,
+    overrides: &HashMap<Vec2, Tile>,
+) -> usize {
     // A second map that traces where the beams have been so far
     let mut beam_paths = Map2d::new_default(map.size, DirSet::new_empty());
     let mut stack = vec![(source_pos, source_dir)];
@@ -133,7 +148,11 @@ fn count_energized(map: &Map2d<Tile>, source_pos: Vec2, source_dir: Dir) -> usiz
         }
 
         beam_paths.get_mut(pos).map(|dir_set| dir_set.insert(dir));
-        match map.get(pos).unwrap_or_default().propagate(dir) {
+        let tile = match overrides.get(&pos) {
+            Some(tile) => *tile,
+            None => map.get(pos).unwrap_or_default(),
+        };
+        match tile.propagate(dir) {
             Propagation::Terminate => (),
             Propagation::Single(dir) => stack.push((pos + dir, dir)),
             Propagation::Double(dir1, dir2) => {
@@ -143,28 +162,73 @@ fn count_energized(map: &Map2d<Tile>, source_pos: Vec2, source_dir: Dir) -> usiz
         }
     }
 
-    beam_paths
-        .data
-        .iter()
-        .filter(|dir_set| !dir_set.is_empty())
-        .count()
+    beam_paths.count(|dir_set| !dir_set.is_empty())
 }
 
 pub fn solve_part_1(map: &Map2d<Tile>) -> usize {
-    count_energized(map, Vec2::new(0, 0), Dir::Right)
+    energized_count(map, Vec2::new(0, 0), Dir::Right)
 }
 
 pub fn solve_part_2(map: &Map2d<Tile>) -> usize {
     // Perhaps possible to do some fancy memoization, but brute forcing 440 edge
     // tile+dir tuples in the real input is fast enough
 
-    let top = (0..map.size().x).map(|x| count_energized(map, Vec2::new(x, 0), Dir::Down));
-    let left = (0..map.size().y).map(|y| count_energized(map, Vec2::new(0, y), Dir::Right));
+    let top = (0..map.size().x).map(|x| energized_count(map, Vec2::new(x, 0), Dir::Down));
+    let left = (0..map.size().y).map(|y| energized_count(map, Vec2::new(0, y), Dir::Right));
     let bottom =
-        (0..map.size().x).map(|x| count_energized(map, Vec2::new(x, map.size().y - 1), Dir::Up));
+        (0..map.size().x).map(|x| energized_count(map, Vec2::new(x, map.size().y - 1), Dir::Up));
     let right =
-        (0..map.size().y).map(|y| count_energized(map, Vec2::new(map.size().x - 1, y), Dir::Left));
+        (0..map.size().y).map(|y| energized_count(map, Vec2::new(map.size().x - 1, y), Dir::Left));
     let all = top.chain(left).chain(bottom).chain(right);
 
     all.max().unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = r".|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....";
+
+    #[test]
+    fn test_part_1() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_1(&input), 46);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_2(&input), 51);
+    }
+
+    #[test]
+    fn test_energized_count_from_edge() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(energized_count(&input, Vec2::new(3, 0), Dir::Down), 51);
+    }
+
+    #[test]
+    fn test_energized_with_override() {
+        let input = parse(".\\.\n...\n...");
+        assert_eq!(energized_count(&input, Vec2::new(0, 0), Dir::Right), 4);
+
+        // Replacing the mirror with an empty tile sends the beam straight out
+        // of the right hand side instead of down through the middle column
+        let overrides = HashMap::from([(Vec2::new(1, 0), Tile::Empty)]);
+        assert_eq!(
+            energized_with_override(&input, Vec2::new(0, 0), Dir::Right, &overrides),
+            3
+        );
+        assert_eq!(energized_count(&input, Vec2::new(0, 0), Dir::Right), 4);
+    }
+}
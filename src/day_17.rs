@@ -64,6 +64,7 @@ pub fn solve_part_1(input: &Map2d<u8>) -> i64 {
         |node| next_nodes(input, node, 1, 3),
     )
     .unwrap()
+    .goal()
     .cost
 }
 
@@ -77,5 +78,57 @@ pub fn solve_part_2(input: &Map2d<u8>) -> i64 {
         |node| next_nodes(input, node, 4, 10),
     )
     .unwrap()
+    .goal()
     .cost
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533";
+
+    #[test]
+    fn test_part_1() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_1(&input), 102);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_2(&input), 94);
+    }
+
+    #[test]
+    fn test_goal_node() {
+        let input = parse(EXAMPLE_INPUT);
+        let goal = graph::dijkstra(
+            DijkstraNode {
+                pos: Vec2::new(0, 0),
+                dir: None,
+            },
+            |node| node.pos == input.size() - Vec2::new(1, 1),
+            |node| next_nodes(&input, node, 1, 3),
+        )
+        .unwrap()
+        .goal();
+
+        // The corner can only be entered moving right or down
+        assert_eq!(goal.node.pos, input.size() - Vec2::new(1, 1));
+        assert!(matches!(goal.node.dir, Some(Dir::Right | Dir::Down)));
+        assert_eq!(goal.cost, 102);
+    }
+}
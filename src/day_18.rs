@@ -1,6 +1,8 @@
 use std::str::FromStr;
 
-use crate::util::{Dir, Vec2};
+use anyhow::bail;
+
+use crate::util::{polygon_area, Dir, Vec2};
 
 #[derive(Debug)]
 pub struct Instruction {
@@ -51,20 +53,32 @@ fn vertices(instructions: impl Iterator<Item = (Dir, i64)>) -> impl Iterator<Ite
     })
 }
 
-pub fn solve(instructions: impl Iterator<Item = (Dir, i64)> + Clone) -> i64 {
-    let vertices = || vertices(instructions.clone());
+/// Checks that the path is made of purely horizontal/vertical segments, and
+/// that it ends back at the origin it started from
+fn validate_path(vertices: impl Iterator<Item = Vec2>) -> anyhow::Result<()> {
+    let mut prev = Vec2::zero();
+    for vertex in vertices {
+        let delta = vertex - prev;
+        if (delta.x != 0) == (delta.y != 0) {
+            bail!("Path segment from {prev:?} to {vertex:?} is not axis aligned");
+        }
+        prev = vertex;
+    }
 
-    // The shoelace formula for the area of a polygon
-    // A = 1/2 * ∑(y_i + y_(i+1_)) * (x_i - x_(i+1_)
-    let shifted = vertices()
-        .skip(1)
-        .chain(std::iter::once(vertices().next().unwrap()));
-    let pairs = vertices().zip(shifted);
-    let mut shoelace_area = 0;
-    for (a, b) in pairs {
-        shoelace_area += (a.y + b.y) * (a.x - b.x)
+    if prev != Vec2::zero() {
+        bail!("Path is not closed, it ends at {prev:?}");
     }
-    shoelace_area /= 2;
+
+    Ok(())
+}
+
+pub fn solve(instructions: impl Iterator<Item = (Dir, i64)> + Clone) -> anyhow::Result<i64> {
+    let vertices = || vertices(instructions.clone());
+
+    // The shoelace approach below is only valid for closed rectilinear paths
+    validate_path(vertices())?;
+
+    let shoelace_area = polygon_area(&vertices().collect::<Vec<_>>());
 
     // The shoelace formula doesn't quite give us the right answer as our
     // indices are effectively at the center of each grid square rather than on
@@ -77,11 +91,11 @@ pub fn solve(instructions: impl Iterator<Item = (Dir, i64)> + Clone) -> i64 {
     let interior_count = shoelace_area - boundary_count / 2 + 1;
 
     // Our actual area is the number of boundary points + the number of interior points
-    boundary_count + interior_count
+    Ok(boundary_count + interior_count)
 }
 
 pub fn solve_part_1(input: &[Instruction]) -> i64 {
-    solve(input.iter().map(|i| (i.dir, i.digit as i64)))
+    solve(input.iter().map(|i| (i.dir, i.digit as i64))).unwrap()
 }
 
 pub fn solve_part_2(input: &[Instruction]) -> i64 {
@@ -97,4 +111,55 @@ pub fn solve_part_2(input: &[Instruction]) -> i64 {
 
         (dir, distance)
     }))
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
+
+    #[test]
+    fn test_part_1() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_1(&input), 62);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_2(&input), 952408144115);
+    }
+
+    #[test]
+    fn test_unclosed_path() {
+        let instructions = [(Dir::Right, 4), (Dir::Down, 4), (Dir::Left, 4)];
+        assert!(solve(instructions.iter().copied()).is_err());
+    }
+
+    #[test]
+    fn test_zero_length_segment() {
+        let instructions = [
+            (Dir::Right, 4),
+            (Dir::Down, 4),
+            (Dir::Left, 0),
+            (Dir::Left, 4),
+            (Dir::Up, 4),
+        ];
+        assert!(solve(instructions.iter().copied()).is_err());
+    }
 }
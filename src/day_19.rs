@@ -1,17 +1,14 @@
 use std::collections::HashMap;
 use std::ops::{Index, IndexMut};
-use std::str::FromStr;
+use std::rc::Rc;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct WfId(usize);
 
-#[derive(Clone, Copy, Debug)]
-enum Property {
-    X,
-    M,
-    A,
-    S,
-}
+/// Index of one of the properties objects are rated on, in the order the
+/// properties are listed in [`Input::properties`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Property(usize);
 
 #[derive(Clone, Copy, Debug)]
 enum Test {
@@ -41,9 +38,9 @@ impl Test {
 
     /// Splits the given range into a range that passes this test and a range
     /// that fails this test.
-    fn test_range(&self, object_range: ObjectRange) -> (Option<ObjectRange>, Option<ObjectRange>) {
+    fn test_range(&self, object_range: &ObjectRange) -> (Option<ObjectRange>, Option<ObjectRange>) {
         match self {
-            Self::Void => (Some(object_range), None),
+            Self::Void => (Some(object_range.clone()), None),
             Self::LessThan { property, value } => object_range.split_lt(*property, *value),
             Self::GreaterThan { property, value } => {
                 let (b, a) = object_range.split_lt(*property, *value + 1);
@@ -53,26 +50,23 @@ impl Test {
     }
 }
 
-impl FromStr for Test {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        assert!(s.is_ascii());
-
-        let property = match &s[0..1] {
-            "x" => Property::X,
-            "m" => Property::M,
-            "a" => Property::A,
-            "s" => Property::S,
-            other => panic!("Invalid property '{other}'"),
-        };
-
-        let value = s[2..].parse().unwrap();
-
-        match &s[1..2] {
-            "<" => Ok(Self::LessThan { property, value }),
-            ">" => Ok(Self::GreaterThan { property, value }),
-            _ => panic!("Invalid test '{s}'"),
+impl Test {
+    fn parse(s: &str, property_ids: &HashMap<String, Property>) -> Self {
+        // Parses strings like "x<10" or "m>100"
+        let op_idx = s
+            .find(['<', '>'])
+            .unwrap_or_else(|| panic!("Invalid test '{s}'"));
+        let (name, rest) = s.split_at(op_idx);
+
+        let property = *property_ids
+            .get(name)
+            .unwrap_or_else(|| panic!("Invalid property '{name}'"));
+        let value = rest[1..].parse().unwrap();
+
+        match &rest[..1] {
+            "<" => Self::LessThan { property, value },
+            ">" => Self::GreaterThan { property, value },
+            _ => unreachable!(),
         }
     }
 }
@@ -91,7 +85,11 @@ struct Instruction {
 }
 
 impl Instruction {
-    fn parse(s: &str, workflow_ids: &HashMap<String, WfId>) -> Self {
+    fn parse(
+        s: &str,
+        workflow_ids: &HashMap<String, WfId>,
+        property_ids: &HashMap<String, Property>,
+    ) -> Self {
         // Parses strings like:
         //   "x<10:A" - if x < 10, destination accept
         //   "m>100:asdf" - if m > 100, destination workflow "asdf"
@@ -104,7 +102,7 @@ impl Instruction {
                 "A" => Destination::Accept,
                 _ => Destination::Workflow(workflow_ids[dest_name]),
             };
-            let test = test.parse().unwrap();
+            let test = Test::parse(test, property_ids);
             Self { test, destination }
         } else {
             let destination = match s {
@@ -141,7 +139,7 @@ impl Workflow {
             .iter()
             .scan(Some(object_range), |object_range, instruction| {
                 if let Some(r) = object_range {
-                    let (pass, fail) = instruction.test.test_range(*r);
+                    let (pass, fail) = instruction.test.test_range(r);
                     *object_range = fail;
                     Some(pass.map(|r| (instruction.destination, r)))
                 } else {
@@ -152,48 +150,32 @@ impl Workflow {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-struct Object {
-    x: i64,
-    m: i64,
-    a: i64,
-    s: i64,
-}
+/// The rating of an object in each property
+#[derive(Clone, Debug)]
+struct Object(Vec<i64>);
 
 impl Object {
-    fn sum(&self) -> i64 {
-        self.x + self.m + self.a + self.s
-    }
-}
+    fn parse(s: &str, property_ids: &HashMap<String, Property>) -> Self {
+        // Parses strings like "{x=787,m=2655,a=1222,s=2876}", leaving any
+        // property that isn't mentioned at 0
 
-impl FromStr for Object {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // Parses strings like "{x=787,m=2655,a=1222,s=2876}"
-
-        let mut object = Object {
-            x: 0,
-            m: 0,
-            a: 0,
-            s: 0,
-        };
+        let mut object = Object(vec![0; property_ids.len()]);
 
         let parts = s.trim_start_matches('{').trim_end_matches('}').split(',');
 
         for part in parts {
-            let (property, value) = part.split_once('=').unwrap();
-            let value = value.parse().unwrap();
-            match property {
-                "x" => object.x = value,
-                "m" => object.m = value,
-                "a" => object.a = value,
-                "s" => object.s = value,
-                _ => panic!("Invalid property '{property}'"),
-            }
+            let (name, value) = part.split_once('=').unwrap();
+            let property = *property_ids
+                .get(name)
+                .unwrap_or_else(|| panic!("Invalid property '{name}'"));
+            object.0[property.0] = value.parse().unwrap();
         }
 
-        Ok(object)
+        object
+    }
+
+    fn sum(&self) -> i64 {
+        self.0.iter().sum()
     }
 }
 
@@ -201,57 +183,64 @@ impl Index<Property> for Object {
     type Output = i64;
 
     fn index(&self, property: Property) -> &Self::Output {
-        match property {
-            Property::X => &self.x,
-            Property::M => &self.m,
-            Property::A => &self.a,
-            Property::S => &self.s,
-        }
+        &self.0[property.0]
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-struct ObjectRange {
-    // Inclusive bounds
-    x: (i64, i64),
-    m: (i64, i64),
-    a: (i64, i64),
-    s: (i64, i64),
+/// A box of objects, with each rating between an inclusive pair of bounds
+#[derive(Clone, Debug)]
+pub struct ObjectRange {
+    /// The name of each property, shared by every range from the same input
+    pub names: Rc<[String]>,
+
+    /// The bounds on each property, in the same order as `names`
+    pub bounds: Vec<(i64, i64)>,
+}
+
+impl std::fmt::Display for ObjectRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (name, (lower, upper))) in self.names.iter().zip(&self.bounds).enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{name}={lower}..={upper}")?;
+        }
+        Ok(())
+    }
 }
 
 impl Index<Property> for ObjectRange {
     type Output = (i64, i64);
 
     fn index(&self, property: Property) -> &Self::Output {
-        match property {
-            Property::X => &self.x,
-            Property::M => &self.m,
-            Property::A => &self.a,
-            Property::S => &self.s,
-        }
+        &self.bounds[property.0]
     }
 }
 
 impl IndexMut<Property> for ObjectRange {
     fn index_mut(&mut self, property: Property) -> &mut Self::Output {
-        match property {
-            Property::X => &mut self.x,
-            Property::M => &mut self.m,
-            Property::A => &mut self.a,
-            Property::S => &mut self.s,
-        }
+        &mut self.bounds[property.0]
     }
 }
 
 impl ObjectRange {
+    /// Every object with each of the named ratings in the range 1..=4000
+    pub fn full(names: Rc<[String]>) -> Self {
+        let bounds = vec![(1, 4000); names.len()];
+        Self { names, bounds }
+    }
+
     /// The total number of distinct objects in this range.
-    fn len(&self) -> i64 {
-        let mut len = 1;
-        for property in &[Property::X, Property::M, Property::A, Property::S] {
-            let (lower, upper) = self[*property];
-            len *= upper - lower + 1;
-        }
-        len
+    pub fn len(&self) -> i64 {
+        self.bounds
+            .iter()
+            .map(|(lower, upper)| upper - lower + 1)
+            .product()
+    }
+
+    /// Whether any property's range is empty, so the range holds no objects
+    pub fn is_empty(&self) -> bool {
+        self.bounds.iter().any(|(lower, upper)| lower > upper)
     }
 
     /// Splits this range into two, one with the given property less than the
@@ -287,16 +276,18 @@ impl ObjectRange {
 
 #[derive(Debug)]
 pub struct Input {
+    /// The names of the properties objects are rated on
+    properties: Rc<[String]>,
     start_workflow: WfId,
     workflows: Vec<Workflow>,
     objects: Vec<Object>,
 }
 
 impl Input {
-    fn final_destination(&self, object: Object) -> Destination {
+    fn final_destination(&self, object: &Object) -> Destination {
         let mut wf = self.start_workflow;
         loop {
-            match self.workflows[wf.0].destination(&object) {
+            match self.workflows[wf.0].destination(object) {
                 Destination::Reject => return Destination::Reject,
                 Destination::Accept => return Destination::Accept,
                 Destination::Workflow(next_wf) => wf = next_wf,
@@ -304,26 +295,92 @@ impl Input {
         }
     }
 
-    fn range_destinations(&self, object_range: ObjectRange) -> Vec<ObjectRange> {
+    /// Runs every object through the workflows once.
+    ///
+    /// Returns the number of accepted objects, the number of rejected objects,
+    /// and the sum of the ratings of all the accepted objects.
+    fn classify_all(&self) -> (usize, usize, i64) {
+        let mut accepted = 0;
+        let mut rejected = 0;
+        let mut accepted_sum = 0;
+
+        for object in &self.objects {
+            match self.final_destination(object) {
+                Destination::Accept => {
+                    accepted += 1;
+                    accepted_sum += object.sum();
+                }
+                Destination::Reject => rejected += 1,
+                Destination::Workflow(_) => unreachable!(),
+            }
+        }
+
+        (accepted, rejected, accepted_sum)
+    }
+
+    /// Pushes the given range through the workflows, splitting it up as needed.
+    ///
+    /// Returns the list of accepted ranges and the list of rejected ranges.
+    fn range_destinations(
+        &self,
+        object_range: ObjectRange,
+    ) -> (Vec<ObjectRange>, Vec<ObjectRange>) {
+        let total_len = object_range.len();
         let mut stack = vec![(self.start_workflow, object_range)];
         let mut accepted = Vec::new();
+        let mut rejected = Vec::new();
 
         while let Some((wf, object_range)) = stack.pop() {
             for (destinationm, object_range) in
                 self.workflows[wf.0].range_destinations(object_range)
             {
                 match destinationm {
-                    Destination::Reject => {}
+                    Destination::Reject => rejected.push(object_range),
                     Destination::Accept => accepted.push(object_range),
                     Destination::Workflow(next_wf) => stack.push((next_wf, object_range)),
                 }
             }
         }
 
+        // Every object in the original range should end up in exactly one of
+        // the accepted/rejected ranges
+        debug_assert_eq!(
+            accepted
+                .iter()
+                .chain(&rejected)
+                .map(|r| r.len())
+                .sum::<i64>(),
+            total_len
+        );
+
+        (accepted, rejected)
+    }
+
+    /// The number of distinct objects in the given range that are accepted,
+    /// and the number that are rejected
+    pub fn count_all(&self, object_range: ObjectRange) -> (i64, i64) {
+        let (accepted, rejected) = self.range_destinations(object_range);
+        (total_len(&accepted), total_len(&rejected))
+    }
+
+    /// Every object with each rating in the range 1..=4000
+    pub fn full_range(&self) -> ObjectRange {
+        ObjectRange::full(self.properties.clone())
+    }
+
+    /// The non-overlapping ranges that together cover every accepted object
+    /// with ratings in 1..=4000
+    pub fn accepted_ranges(&self) -> Vec<ObjectRange> {
+        let (accepted, _rejected) = self.range_destinations(self.full_range());
         accepted
     }
 }
 
+/// The total number of objects in a set of ranges, assumed not to overlap
+fn total_len(ranges: &[ObjectRange]) -> i64 {
+    ranges.iter().map(|r| r.len()).sum()
+}
+
 impl AsRef<Input> for Input {
     fn as_ref(&self) -> &Input {
         self
@@ -341,6 +398,36 @@ pub fn parse(input: &str) -> Input {
 
     let start_workflow = workflow_ids["in"];
 
+    // Number the properties in the order they're first mentioned, looking at
+    // the objects first so that the usual "x,m,a,s" ordering is kept
+    let mut properties = Vec::new();
+    let property_names = objects
+        .lines()
+        .flat_map(|line| {
+            line.trim_start_matches('{')
+                .trim_end_matches('}')
+                .split(',')
+                .filter_map(|part| part.split_once('=').map(|(name, _)| name))
+        })
+        .chain(workflows.lines().flat_map(|line| {
+            let (_, instructions) = line.split_once('{').unwrap();
+            instructions
+                .trim_end_matches('}')
+                .split(',')
+                .filter_map(|s| s.find(['<', '>']).map(|idx| &s[..idx]))
+        }));
+    for name in property_names {
+        if !properties.iter().any(|p| p == name) {
+            properties.push(name.to_owned());
+        }
+    }
+
+    let property_ids = properties
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.clone(), Property(i)))
+        .collect::<HashMap<_, _>>();
+
     let workflows = workflows
         .lines()
         .map(|line| {
@@ -348,15 +435,19 @@ pub fn parse(input: &str) -> Input {
             let instructions = instructions
                 .trim_end_matches('}')
                 .split(',')
-                .map(|s| Instruction::parse(s, &workflow_ids))
+                .map(|s| Instruction::parse(s, &workflow_ids, &property_ids))
                 .collect();
             Workflow(instructions)
         })
         .collect();
 
-    let objects = objects.lines().map(|line| line.parse().unwrap()).collect();
+    let objects = objects
+        .lines()
+        .map(|line| Object::parse(line, &property_ids))
+        .collect();
 
     Input {
+        properties: properties.into(),
         start_workflow,
         workflows,
         objects,
@@ -364,26 +455,109 @@ pub fn parse(input: &str) -> Input {
 }
 
 pub fn solve_part_1(input: &Input) -> i64 {
-    let mut sum = 0;
-    for object in &input.objects {
-        if input.final_destination(*object) == Destination::Accept {
-            sum += object.sum();
-        }
-    }
-    sum
+    let (_accepted, _rejected, accepted_sum) = input.classify_all();
+    accepted_sum
 }
 
 pub fn solve_part_2(input: &Input) -> i64 {
-    let range = ObjectRange {
-        x: (1, 4000),
-        m: (1, 4000),
-        a: (1, 4000),
-        s: (1, 4000),
-    };
-
-    input
-        .range_destinations(range)
-        .iter()
-        .map(|r| r.len())
-        .sum()
+    let (accepted, _rejected) = input.count_all(input.full_range());
+    accepted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "px{a<2006:qkq,m>2090:A,rfg}
+pv{a>1716:R,A}
+lnx{m>1548:A,A}
+rfg{s<537:gd,x>2440:R,A}
+qs{s>3448:A,lnx}
+qkq{x<1416:A,crn}
+crn{x>2662:A,R}
+in{s<1351:px,qqz}
+qqz{s>2770:qs,m<1801:hdj,R}
+gd{a>3333:R,R}
+hdj{m>838:A,pv}
+
+{x=787,m=2655,a=1222,s=2876}
+{x=1679,m=44,a=2067,s=496}
+{x=2036,m=264,a=79,s=2244}
+{x=2461,m=1339,a=466,s=291}
+{x=2127,m=1623,a=2188,s=1013}";
+
+    #[test]
+    fn test_classify_all() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(input.classify_all(), (3, 2, 19114));
+    }
+
+    #[test]
+    fn test_count_all() {
+        let input = parse(EXAMPLE_INPUT);
+        let range = input.full_range();
+
+        let (accepted, rejected) = input.count_all(range.clone());
+        assert_eq!(accepted, 167409079868000);
+        assert_eq!(accepted + rejected, range.len());
+    }
+
+    #[test]
+    fn test_part_1() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_1(&input), 19114);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_2(&input), 167409079868000);
+    }
+
+    #[test]
+    fn test_object_range_display_and_total_len() {
+        let input = parse(EXAMPLE_INPUT);
+        let range = input.full_range();
+        assert_eq!(
+            range.to_string(),
+            "x=1..=4000 m=1..=4000 a=1..=4000 s=1..=4000"
+        );
+
+        let (accepted, _) = input.range_destinations(range);
+        assert_eq!(total_len(&accepted), 167409079868000);
+    }
+
+    #[test]
+    fn test_accepted_ranges() {
+        let input = parse(EXAMPLE_INPUT);
+        let accepted = input.accepted_ranges();
+        assert!(!accepted.is_empty());
+        assert_eq!(total_len(&accepted), 167409079868000);
+        assert_eq!(total_len(&accepted), input.count_all(input.full_range()).0);
+    }
+
+    #[test]
+    fn test_three_properties() {
+        let input = parse(
+            "in{red<101:A,blue>3000:low,R}
+low{green<2001:A,R}
+
+{red=50,green=10,blue=3500}
+{red=200,green=2500,blue=3500}
+{red=200,green=10,blue=3500}",
+        );
+        assert_eq!(&*input.properties, ["red", "green", "blue"]);
+        assert_eq!(input.classify_all(), (2, 1, 3560 + 3710));
+
+        // red <= 100 is accepted outright, otherwise need blue > 3000 and
+        // green <= 2000
+        let expected = 100 * 4000 * 4000 + 3900 * 2000 * 1000;
+        assert_eq!(solve_part_2(&input), expected);
+        assert_eq!(input.full_range().len(), 4000 * 4000 * 4000);
+        assert!(!input.full_range().is_empty());
+
+        let mut empty = input.full_range();
+        empty.bounds[1] = (2001, 2000);
+        assert!(empty.is_empty());
+    }
 }
@@ -1,14 +1,93 @@
+use std::ops::{Index, IndexMut};
 use std::str::FromStr;
 
 use anyhow::anyhow;
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+impl Color {
+    pub const ALL: [Color; 3] = [Color::Red, Color::Green, Color::Blue];
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Guess {
     red: u32,
     green: u32,
     blue: u32,
 }
 
+impl Guess {
+    /// The componentwise maximum of the two guesses
+    pub fn max(self, other: Self) -> Self {
+        Guess {
+            red: self.red.max(other.red),
+            green: self.green.max(other.green),
+            blue: self.blue.max(other.blue),
+        }
+    }
+
+    /// The product of the number of cubes of each color
+    pub fn power(&self) -> u32 {
+        self.red * self.green * self.blue
+    }
+}
+
+impl Index<Color> for Guess {
+    type Output = u32;
+
+    fn index(&self, color: Color) -> &Self::Output {
+        match color {
+            Color::Red => &self.red,
+            Color::Green => &self.green,
+            Color::Blue => &self.blue,
+        }
+    }
+}
+
+impl IndexMut<Color> for Guess {
+    fn index_mut(&mut self, color: Color) -> &mut Self::Output {
+        match color {
+            Color::Red => &mut self.red,
+            Color::Green => &mut self.green,
+            Color::Blue => &mut self.blue,
+        }
+    }
+}
+
+/// The number of cubes of each color in the bag the games are played with
+#[derive(Clone, Copy, Debug)]
+pub struct Bag {
+    pub red: u32,
+    pub green: u32,
+    pub blue: u32,
+}
+
+impl Bag {
+    /// The bag from the puzzle
+    pub const DEFAULT: Bag = Bag {
+        red: 12,
+        green: 13,
+        blue: 14,
+    };
+}
+
+impl Index<Color> for Bag {
+    type Output = u32;
+
+    fn index(&self, color: Color) -> &Self::Output {
+        match color {
+            Color::Red => &self.red,
+            Color::Green => &self.green,
+            Color::Blue => &self.blue,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Game {
     id: u32,
@@ -48,13 +127,13 @@ impl FromStr for Game {
                     .split_once(' ')
                     .ok_or_else(|| anyhow!("Invalid guess string"))?;
 
-                let count = count_str.parse::<u32>()?;
-                match color_str {
-                    "red" => guess.red = count,
-                    "green" => guess.green = count,
-                    "blue" => guess.blue = count,
+                let color = match color_str {
+                    "red" => Color::Red,
+                    "green" => Color::Green,
+                    "blue" => Color::Blue,
                     _ => return Err(anyhow!("Invalid color string")),
-                }
+                };
+                guess[color] = count_str.parse::<u32>()?;
             }
             guesses.push(guess);
         }
@@ -67,28 +146,115 @@ pub fn parse(input: &str) -> Vec<Game> {
     input.lines().map(|line| line.parse().unwrap()).collect()
 }
 
+/// The largest number of cubes of `color` shown in any of the game's guesses
+fn max_shown(game: &Game, color: Color) -> u32 {
+    game.guesses.iter().map(|g| g[color]).max().unwrap_or(0)
+}
+
+/// The sum of the IDs of the games that could have been played with `bag`
+pub fn solve_part_1_with(input: &[Game], bag: &Bag) -> u32 {
+    input
+        .iter()
+        .filter(|game| Color::ALL.iter().all(|&c| max_shown(game, c) <= bag[c]))
+        .map(|g| g.id)
+        .sum()
+}
+
 pub fn solve_part_1(input: &[Game]) -> u32 {
+    solve_part_1_with(input, &Bag::DEFAULT)
+}
+
+pub fn solve_part_2(input: &[Game]) -> u32 {
     input
         .iter()
-        .filter(|game| {
+        .map(|game| {
             game.guesses
                 .iter()
-                .all(|guess| guess.red <= 12 && guess.green <= 13 && guess.blue <= 14)
+                .copied()
+                .fold(Guess::default(), Guess::max)
+                .power()
         })
-        .map(|g| g.id)
         .sum()
 }
 
-pub fn solve_part_2(input: &[Game]) -> u32 {
-    let mut sum = 0;
-    for game in input {
-        // The maximum red guess, green guess, and blue guess in this game:
-        let max_red = game.guesses.iter().map(|g| g.red).max().unwrap_or(0);
-        let max_green = game.guesses.iter().map(|g| g.green).max().unwrap_or(0);
-        let max_blue = game.guesses.iter().map(|g| g.blue).max().unwrap_or(0);
-
-        sum += max_red * max_green * max_blue;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn test_part_1() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_1(&input), 8);
     }
 
-    sum
+    #[test]
+    fn test_part_1_with() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_1_with(&input, &Bag::DEFAULT), 8);
+
+        // Enough red for game 3, and enough blue for game 4
+        let bigger = Bag {
+            red: 20,
+            green: 13,
+            blue: 15,
+        };
+        assert_eq!(solve_part_1_with(&input, &bigger), 15);
+
+        let empty = Bag {
+            red: 0,
+            green: 0,
+            blue: 0,
+        };
+        assert_eq!(solve_part_1_with(&input, &empty), 0);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_2(&input), 2286);
+    }
+
+    #[test]
+    fn test_guess_max_and_power() {
+        let a = Guess {
+            red: 4,
+            green: 0,
+            blue: 3,
+        };
+        let b = Guess {
+            red: 1,
+            green: 2,
+            blue: 6,
+        };
+        let max = Guess {
+            red: 4,
+            green: 2,
+            blue: 6,
+        };
+        assert_eq!(a.max(b), max);
+        assert_eq!(b.max(a), max);
+        assert_eq!(a.max(Guess::default()), a);
+
+        assert_eq!(max.power(), 48);
+        assert_eq!(a.power(), 0);
+    }
+
+    #[test]
+    fn test_color_index() {
+        let games = parse(EXAMPLE_INPUT);
+        let game = &games[0];
+
+        let maxes = Color::ALL.map(|c| game.guesses.iter().map(|g| g[c]).max().unwrap());
+        let max_red = game.guesses.iter().map(|g| g.red).max().unwrap();
+        let max_green = game.guesses.iter().map(|g| g.green).max().unwrap();
+        let max_blue = game.guesses.iter().map(|g| g.blue).max().unwrap();
+        assert_eq!(maxes, [max_red, max_green, max_blue]);
+        assert_eq!(maxes, [4, 2, 6]);
+    }
 }
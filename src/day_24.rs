@@ -1,6 +1,6 @@
-use crate::util::pairs;
+use crate::util::{pairs, parse_ints};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 struct Vec3 {
     x: f64,
     y: f64,
@@ -57,7 +57,7 @@ impl std::ops::Mul<f64> for Vec3 {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Hailstone {
     pos: Vec3,
     vel: Vec3,
@@ -66,18 +66,18 @@ pub struct Hailstone {
 pub fn parse(input: &str) -> Vec<Hailstone> {
     // Input lines like:
     // px, py, pz @ vx, vy, vz
-    let parse_vec3 = |s: &str| {
-        let mut s = s.split(", ").map(|x| x.trim().parse().unwrap());
-        Vec3 {
-            x: s.next().unwrap(),
-            y: s.next().unwrap(),
-            z: s.next().unwrap(),
-        }
+    let parse_vec3 = |s: &str| match parse_ints(s)[..] {
+        [x, y, z] => Vec3 {
+            x: x as f64,
+            y: y as f64,
+            z: z as f64,
+        },
+        _ => panic!("Expected three components in {s:?}"),
     };
 
     let mut stones = Vec::new();
     for line in input.lines() {
-        let (pos, vel) = line.split_once(" @ ").unwrap();
+        let (pos, vel) = line.split_once('@').unwrap();
         stones.push(Hailstone {
             pos: parse_vec3(pos),
             vel: parse_vec3(vel),
@@ -213,3 +213,29 @@ pub fn solve_part_2(input: &[Hailstone]) -> i64 {
 
     (a.x + a.y + a.z) as i64
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let expected = vec![Hailstone {
+            pos: Vec3 {
+                x: 1.0,
+                y: 2.0,
+                z: 3.0,
+            },
+            vel: Vec3 {
+                x: 4.0,
+                y: -5.0,
+                z: 6.0,
+            },
+        }];
+
+        assert_eq!(parse("1, 2, 3 @ 4, -5, 6"), expected);
+        assert_eq!(parse("1,2,3@4,-5,6"), expected);
+        assert_eq!(parse("1,  2,   3   @   4,  -5,  6"), expected);
+        assert_eq!(parse("1 ,2 ,3 @4 ,-5 ,6\n"), expected);
+    }
+}
@@ -1,6 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 
-use rand::{rngs::SmallRng, Rng, SeedableRng};
+#[cfg(test)]
+use rand::{seq::SliceRandom, Rng};
+
+#[cfg(test)]
+use crate::util::graph::UnionFind;
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 struct NodeId(usize);
@@ -30,6 +34,54 @@ impl AsRef<Graph> for Graph {
     }
 }
 
+impl Graph {
+    /// Removes the given edges (in either orientation) and counts the nodes in
+    /// each connected component that remains.
+    ///
+    /// Returns the two component sizes if exactly two components remain,
+    /// otherwise None.
+    #[cfg(test)]
+    fn partition_sizes_after_removing(&self, cut: &[(NodeId, NodeId)]) -> Option<(usize, usize)> {
+        let is_cut = |edge: &Edge| {
+            cut.iter().any(|&(a, b)| {
+                (edge.source == a && edge.sink == b) || (edge.source == b && edge.sink == a)
+            })
+        };
+
+        let mut adjacent = vec![Vec::new(); self.name_to_id.len()];
+        for edge in self.edges.iter().filter(|e| !is_cut(e)) {
+            adjacent[edge.source.0].push(edge.sink);
+            adjacent[edge.sink.0].push(edge.source);
+        }
+
+        let mut visited = HashSet::new();
+        let mut sizes = Vec::new();
+        for start in (0..self.name_to_id.len()).map(NodeId) {
+            if !visited.insert(start) {
+                continue;
+            }
+
+            let mut size = 0;
+            let mut queue = std::collections::VecDeque::from([start]);
+            while let Some(node) = queue.pop_front() {
+                size += 1;
+                for next in &adjacent[node.0] {
+                    if visited.insert(*next) {
+                        queue.push_back(*next);
+                    }
+                }
+            }
+
+            sizes.push(size);
+        }
+
+        match sizes[..] {
+            [left, right] => Some((left, right)),
+            _ => None,
+        }
+    }
+}
+
 pub fn parse(input: &str) -> Graph {
     // Input like:
     //
@@ -81,80 +133,198 @@ pub fn parse(input: &str) -> Graph {
 
 /// A single trial of the Karger Algorithm
 ///
-/// Returns the number of nodes on the left/right of the cut, and the number of
-/// edges that cross the cut
-fn karger_trial(g: &Graph) -> (usize, usize, usize) {
-    let mut g = g.clone();
-    let mut merged_nodes = (0..g.name_to_id.len())
-        .map(|i| NodeId(i))
-        .map(|id| (id, 1))
-        .collect::<HashMap<_, _>>();
-    let mut rng = SmallRng::from_entropy();
-
-    // The next ID we'll use for new merged nodes
-    let mut next_id = NodeId(g.name_to_id.len());
-
-    while merged_nodes.len() > 2 {
-        // Pick a random edge to contract
-        let edge_idx = rng.gen_range(0..g.edges.len());
-        let edge = g.edges.remove(edge_idx);
-
-        // Remove any edges identical to the one we're contracting
-        g.edges.retain(|e| !{
-            (e.source == edge.source && e.sink == edge.sink)
-                || (e.source == edge.sink && e.sink == edge.source)
-        });
-
-        // The edge previously connected two nodes together. We're going to
-        // collapse the edge such that those two nodes are merged into one new
-        // node
-        let left_id = edge.source;
-        let right_id = edge.sink;
-        let merged_id = next_id;
-        next_id = NodeId(next_id.0 + 1);
-
-        // Record which nodes are in the merged node set
-        let mut merged = merged_nodes.remove(&left_id).unwrap();
-        merged += merged_nodes.remove(&right_id).unwrap();
-        merged_nodes.insert(merged_id, merged);
-
-        // Update any edges that reference the old left/right nodes to reference
-        // the new merged node instead
-        for edge in &mut g.edges {
-            if edge.source == left_id {
-                edge.source = merged_id;
-            }
-            if edge.sink == left_id {
-                edge.sink = merged_id;
+/// Returns the sizes of the two sides of the cut found by this trial, and the
+/// number of original edges crossing it. No longer used by the solution, but
+/// kept to cross-check [`min_cut`] in tests.
+#[cfg(test)]
+fn karger_trial(g: &Graph, rng: &mut impl Rng) -> (usize, usize, usize) {
+    // Contracting edges in a random order, skipping any that have already
+    // become self-loops, is equivalent to repeatedly picking a random edge from
+    // the contracted graph
+    let mut edges = g.edges.clone();
+    edges.shuffle(rng);
+
+    let mut merged = UnionFind::new(g.name_to_id.len());
+    for edge in &edges {
+        if merged.component_count() <= 2 {
+            break;
+        }
+        merged.union(edge.source.0, edge.sink.0);
+    }
+
+    let node_count = g.name_to_id.len();
+    let left_root = merged.find(0);
+    let left = (0..node_count)
+        .filter(|&node| merged.find(node) == left_root)
+        .count();
+
+    let cut = g
+        .edges
+        .iter()
+        .filter(|edge| merged.find(edge.source.0) != merged.find(edge.sink.0))
+        .count();
+
+    (left, node_count - left, cut)
+}
+
+/// Exhaustively searches for the smallest set of at most `max_cut` edges whose
+/// removal splits the graph in two, returning the resulting component sizes.
+///
+/// Only intended for checking the randomized solver against small graphs, so
+/// refuses (with None) graphs of more than 100 edges.
+#[cfg(test)]
+fn min_cut_bruteforce(graph: &Graph, max_cut: usize) -> Option<(usize, usize)> {
+    fn search(
+        graph: &Graph,
+        first_edge: usize,
+        cut: &mut Vec<(NodeId, NodeId)>,
+        cut_size: usize,
+    ) -> Option<(usize, usize)> {
+        if cut.len() == cut_size {
+            return graph.partition_sizes_after_removing(cut);
+        }
+
+        for idx in first_edge..graph.edges.len() {
+            let edge = graph.edges[idx];
+            cut.push((edge.source, edge.sink));
+            let result = search(graph, idx + 1, cut, cut_size);
+            cut.pop();
+
+            if result.is_some() {
+                return result;
             }
-            if edge.source == right_id {
-                edge.source = merged_id;
+        }
+
+        None
+    }
+
+    if graph.edges.len() > 100 {
+        return None;
+    }
+
+    (1..=max_cut).find_map(|cut_size| search(graph, 0, &mut Vec::new(), cut_size))
+}
+
+/// The global minimum cut of the graph, found by the Stoer-Wagner algorithm.
+///
+/// Returns the weight of the cut, along with the nodes on one side of it.
+fn stoer_wagner(graph: &Graph) -> (usize, Vec<NodeId>) {
+    let node_count = graph.name_to_id.len();
+
+    // Edge weights between the merged nodes that remain, indexed by the ID of
+    // the node each merged node started out as
+    let mut adjacent = vec![HashMap::<usize, usize>::new(); node_count];
+    for edge in &graph.edges {
+        *adjacent[edge.source.0].entry(edge.sink.0).or_default() += 1;
+        *adjacent[edge.sink.0].entry(edge.source.0).or_default() += 1;
+    }
+
+    // The original nodes making up each merged node
+    let mut members = (0..node_count)
+        .map(|id| vec![NodeId(id)])
+        .collect::<Vec<_>>();
+    let mut remaining = (0..node_count).collect::<Vec<_>>();
+
+    let mut best: Option<(usize, Vec<NodeId>)> = None;
+    while remaining.len() > 1 {
+        // Maximum adjacency search: repeatedly add the node most tightly
+        // connected to those added so far
+        let mut connectivity = HashMap::<usize, usize>::new();
+        let mut added = HashSet::new();
+        let mut queue = BinaryHeap::new();
+        let mut order = Vec::with_capacity(remaining.len());
+
+        // The search can't reach other components of a disconnected graph, so
+        // start again from any node it missed, with no connection to the rest
+        for &start in &remaining {
+            if added.contains(&start) {
+                continue;
             }
-            if edge.sink == right_id {
-                edge.sink = merged_id;
+            queue.push((0, start));
+
+            while let Some((weight, node)) = queue.pop() {
+                if !added.insert(node) {
+                    continue;
+                }
+                order.push((node, weight));
+
+                for (&next, &edge_weight) in &adjacent[node] {
+                    if !added.contains(&next) {
+                        let next_weight = connectivity.entry(next).or_default();
+                        *next_weight += edge_weight;
+                        queue.push((*next_weight, next));
+                    }
+                }
             }
+        }
 
-            assert_ne!(edge.source, edge.sink);
+        // The cut of the phase separates the last node added from the rest
+        let (last, cut_weight) = order[order.len() - 1];
+        let (second_last, _) = order[order.len() - 2];
+        if best.as_ref().is_none_or(|(weight, _)| cut_weight < *weight) {
+            best = Some((cut_weight, members[last].clone()));
         }
+
+        // Merge the last node into the second to last
+        let last_edges = std::mem::take(&mut adjacent[last]);
+        for (next, edge_weight) in last_edges {
+            adjacent[next].remove(&last);
+            if next != second_last {
+                *adjacent[second_last].entry(next).or_default() += edge_weight;
+                *adjacent[next].entry(second_last).or_default() += edge_weight;
+            }
+        }
+
+        let last_members = std::mem::take(&mut members[last]);
+        members[second_last].extend(last_members);
+        remaining.retain(|&node| node != last);
     }
 
-    let left = g.edges[0].source;
-    let right = g.edges[0].sink;
+    best.expect("Graph must have at least two nodes")
+}
 
-    (
-        merged_nodes.remove(&left).unwrap(),
-        merged_nodes.remove(&right).unwrap(),
-        g.edges.len(),
-    )
+/// The size of the minimum cut of the graph, along with the sizes of the two
+/// partitions it leaves
+fn min_cut(graph: &Graph) -> (usize, usize, usize) {
+    let (cut, side) = stoer_wagner(graph);
+    (cut, side.len(), graph.name_to_id.len() - side.len())
+}
+
+/// The original edges crossing the minimum cut of the graph
+fn min_cut_edges(graph: &Graph) -> Vec<Edge> {
+    let (_, side) = stoer_wagner(graph);
+    let side = side.into_iter().collect::<HashSet<_>>();
+
+    graph
+        .edges
+        .iter()
+        .filter(|edge| side.contains(&edge.source) != side.contains(&edge.sink))
+        .copied()
+        .collect()
+}
+
+/// The names of the two components joined by each of the given edges
+fn wire_names<'a>(graph: &'a Graph, edges: &[Edge]) -> Vec<(&'a str, &'a str)> {
+    let id_to_name = graph
+        .name_to_id
+        .iter()
+        .map(|(name, id)| (*id, name.as_str()))
+        .collect::<HashMap<_, _>>();
+
+    edges
+        .iter()
+        .map(|edge| (id_to_name[&edge.source], id_to_name[&edge.sink]))
+        .collect()
 }
 
 pub fn solve_part_1(graph: &Graph) -> usize {
-    let (left, right) = loop {
-        let (left, right, cut) = karger_trial(graph);
-        if cut == 3 {
-            break (left, right);
-        }
-    };
+    let (cut, left, right) = min_cut(graph);
+    debug_assert_eq!(
+        cut,
+        3,
+        "Expected to cut exactly three wires, found {:?}",
+        wire_names(graph, &min_cut_edges(graph))
+    );
 
     left * right
 }
@@ -166,6 +336,7 @@ pub fn solve_part_2(_graph: &Graph) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::{rngs::SmallRng, SeedableRng};
 
     const EXAMPLE_INPUT: &str = "jqt: rhn xhk nvd
 rsh: frs pzl lsr
@@ -186,4 +357,101 @@ frs: qnr lhk lsr";
         let g = parse(EXAMPLE_INPUT);
         dbg!(g);
     }
+
+    #[test]
+    fn test_partition_sizes_after_removing() {
+        let g = parse(EXAMPLE_INPUT);
+        let id = |name: &str| g.name_to_id[name];
+
+        let cut = [
+            (id("hfx"), id("pzl")),
+            (id("bvb"), id("cmg")),
+            (id("nvd"), id("jqt")),
+        ];
+        let (left, right) = g.partition_sizes_after_removing(&cut).unwrap();
+        assert_eq!(left * right, 54);
+
+        // Removing only some of the cut leaves the graph in one piece
+        assert_eq!(g.partition_sizes_after_removing(&cut[..2]), None);
+    }
+
+    #[test]
+    fn test_part_1() {
+        let g = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_1(&g), 54);
+    }
+
+    #[test]
+    fn test_min_cut_bruteforce() {
+        let g = parse(EXAMPLE_INPUT);
+        let (left, right) = min_cut_bruteforce(&g, 3).unwrap();
+        assert_eq!(left * right, solve_part_1(&g));
+        assert_eq!(left * right, 54);
+
+        // The example has no cut of fewer than three edges
+        assert_eq!(min_cut_bruteforce(&g, 2), None);
+    }
+
+    #[test]
+    fn test_min_cut() {
+        let g = parse(EXAMPLE_INPUT);
+        let (cut, left, right) = min_cut(&g);
+        assert_eq!(cut, 3);
+        assert_eq!(left * right, 54);
+
+        // The randomized algorithm agrees once it happens on the minimum cut,
+        // which it finds with reasonable probability on each trial
+        let mut rng = SmallRng::seed_from_u64(25);
+        let (left, right) = (0..1000)
+            .find_map(|_| {
+                let (left, right, cut) = karger_trial(&g, &mut rng);
+                (cut == 3).then_some((left, right))
+            })
+            .expect("No trial found the minimum cut");
+        assert_eq!(left * right, 54);
+    }
+
+    #[test]
+    fn test_min_cut_wire_names() {
+        let g = parse(EXAMPLE_INPUT);
+        let cut = min_cut_edges(&g);
+        assert_eq!(cut.len(), 3);
+
+        let expected = [("hfx", "pzl"), ("bvb", "cmg"), ("nvd", "jqt")];
+        for (a, b) in wire_names(&g, &cut) {
+            assert!(
+                expected.contains(&(a, b)) || expected.contains(&(b, a)),
+                "Unexpected wire {a}/{b}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_min_cut_disconnected() {
+        // Two disjoint triangles need no edges cut to separate them
+        let g = parse("a: b c\nb: c\nd: e f\ne: f");
+        let (cut, left, right) = min_cut(&g);
+        assert_eq!(cut, 0);
+        assert_eq!((left, right), (3, 3));
+    }
+
+    #[test]
+    fn test_karger_trial() {
+        let g = parse(EXAMPLE_INPUT);
+
+        // Every trial finds some cut, never smaller than the minimum, and the
+        // minimum cut turns up with reasonable probability
+        let mut rng = SmallRng::seed_from_u64(25);
+        let trials = (0..200)
+            .map(|_| karger_trial(&g, &mut rng))
+            .collect::<Vec<_>>();
+        assert!(trials.iter().all(|&(_, _, cut)| cut >= 3));
+        assert!(trials.iter().any(|&(_, _, cut)| cut == 3));
+
+        // Both sides of the cut are non-empty and cover every node
+        for (left, right, _) in trials {
+            assert!(left > 0 && right > 0);
+            assert_eq!(left + right, g.name_to_id.len());
+        }
+    }
 }
@@ -97,7 +97,78 @@ pub fn parse(input: &str) -> Vec<Line> {
     input.lines().map(|line| line.parse().unwrap()).collect()
 }
 
+/// For each line, a bitset of which columns hold a symbol, with column `i` in
+/// bit `i % 64` of word `i / 64`
+fn symbol_columns(input: &[Line]) -> Vec<Vec<u64>> {
+    let width = input
+        .iter()
+        .flat_map(|line| line.0.iter())
+        .map(|element| match element {
+            Element::Number(number) => number.range.end,
+            Element::Symbol(symbol) => symbol.idx + 1,
+        })
+        .max()
+        .unwrap_or(0);
+
+    input
+        .iter()
+        .map(|line| {
+            let mut columns = vec![0; width.div_ceil(64)];
+            for idx in line.symbol_indexes() {
+                columns[idx / 64] |= 1 << (idx % 64);
+            }
+            columns
+        })
+        .collect()
+}
+
+/// Whether any of the columns in `range` are set in the bitset, treating
+/// columns past the end of the bitset as unset
+fn any_set(columns: &[u64], range: std::ops::Range<usize>) -> bool {
+    let end = range.end.min(columns.len() * 64);
+    let mut start = range.start;
+    while start < end {
+        // The columns of `range` within the word containing `start`
+        let len = (end - start).min(64 - start % 64);
+        let mask = u64::MAX >> (64 - len);
+        if (columns[start / 64] >> (start % 64)) & mask != 0 {
+            return true;
+        }
+        start += len;
+    }
+
+    false
+}
+
+/// Every number adjacent to a symbol, including diagonally, in reading order
+pub fn part_numbers(input: &[Line]) -> Vec<u32> {
+    let symbol_columns = symbol_columns(input);
+
+    let mut part_numbers = Vec::new();
+    for i in 0..input.len() {
+        // The symbol columns of this line and the lines either side of it
+        let rows = &symbol_columns[i.saturating_sub(1)..(i + 2).min(input.len())];
+
+        for number in input[i].numbers() {
+            let range = number.expanded_range();
+            let adjacent = rows.iter().any(|row| any_set(row, range.clone()));
+            if adjacent {
+                part_numbers.push(number.value);
+            }
+        }
+    }
+
+    part_numbers
+}
+
 pub fn solve_part_1(input: &[Line]) -> u32 {
+    part_numbers(input).iter().sum()
+}
+
+/// As [`solve_part_1`], but checking each number against a sorted list of the
+/// nearby symbols rather than the precomputed bitsets. Kept to benchmark
+/// against.
+pub fn solve_part_1_naive(input: &[Line]) -> u32 {
     let mut sum = 0;
     let mut symbols = Vec::new();
     for i in 0..input.len() {
@@ -122,12 +193,12 @@ pub fn solve_part_1(input: &[Line]) -> u32 {
     sum
 }
 
-pub fn solve_part_2(input: &[Line]) -> u32 {
-    let mut sum = 0;
+/// The product of the two numbers adjacent to each '*' symbol that is adjacent
+/// to exactly two numbers, in reading order of the symbols
+pub fn gear_ratios(input: &[Line]) -> Vec<u32> {
+    let mut ratios = Vec::new();
     let mut numbers = Vec::new();
 
-    // For each '*' symbol, if it is adjacent to exactly two numbers, multiply
-    // those numbers together and add the result to the sum
     for i in 0..input.len() {
         numbers.clear();
         numbers.extend(input[i].numbers());
@@ -153,14 +224,18 @@ pub fn solve_part_2(input: &[Line]) -> u32 {
             let third = numbers.next();
             match (first, second, third) {
                 (Some(first), Some(second), None) => {
-                    sum += first * second;
+                    ratios.push(first * second);
                 }
                 _ => {}
             }
         }
     }
 
-    sum
+    ratios
+}
+
+pub fn solve_part_2(input: &[Line]) -> u32 {
+    gear_ratios(input).iter().sum()
 }
 
 #[cfg(test)]
@@ -218,6 +293,87 @@ mod tests {
         assert_eq!(solve_part_1(&input), 4361);
     }
 
+    #[test]
+    fn test_any_set() {
+        // Columns 3, 63, 64 and 130
+        let columns = [1 << 3 | 1 << 63, 1, 1 << 2];
+        assert!(any_set(&columns, 0..4));
+        assert!(!any_set(&columns, 0..3));
+        assert!(!any_set(&columns, 4..63));
+        assert!(any_set(&columns, 60..70));
+        assert!(any_set(&columns, 64..65));
+        assert!(!any_set(&columns, 65..130));
+        assert!(any_set(&columns, 65..131));
+
+        // Past the end of the bitset
+        assert!(!any_set(&columns, 131..500));
+        assert!(!any_set(&columns, 5..5));
+    }
+
+    #[test]
+    fn test_part_1_naive() {
+        let input = parse(
+            "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..",
+        );
+        assert_eq!(solve_part_1_naive(&input), 4361);
+
+        // Wide enough for numbers and symbols to straddle the bitset's words
+        let row = |offset: usize, text: &str| {
+            format!(
+                "{}{text}{}",
+                ".".repeat(offset),
+                ".".repeat(140 - offset - text.len())
+            )
+        };
+        let input = [
+            row(60, "1234....42"),
+            row(63, "*.....#"),
+            row(126, "77...9"),
+            row(127, "+"),
+        ]
+        .join("\n");
+        let input = parse(&input);
+        assert_eq!(solve_part_1(&input), 1234 + 42 + 77);
+        assert_eq!(solve_part_1(&input), solve_part_1_naive(&input));
+    }
+
+    #[test]
+    fn test_part_1_edges() {
+        // Symbols touching numbers only diagonally, and at the very edges of
+        // the schematic
+        let input = parse(
+            "1......2
+.#....#.
+........
+3.....4.
+.......$",
+        );
+
+        assert_eq!(solve_part_1(&input), 1 + 2 + 4);
+    }
+
+    #[test]
+    fn test_part_numbers() {
+        // 12 and 56 only touch the symbol diagonally, 9 and 78 don't touch it
+        let input = parse(
+            "12...9
+..#...
+...56.
+78....",
+        );
+
+        assert_eq!(part_numbers(&input), vec![12, 56]);
+    }
+
     #[test]
     fn test_part_2() {
         let input = parse(
@@ -235,4 +391,23 @@ mod tests {
 
         assert_eq!(solve_part_2(&input), 467835);
     }
+
+    #[test]
+    fn test_gear_ratios() {
+        let input = parse(
+            "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..",
+        );
+
+        // The '*' next to 617 only touches one number, so isn't a gear
+        assert_eq!(gear_ratios(&input), vec![467 * 35, 755 * 598]);
+    }
 }
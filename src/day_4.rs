@@ -35,6 +35,34 @@ impl FromStr for Card {
     }
 }
 
+/// The set of numbers as a 256 bit bitset, with bit `n % 128` of word
+/// `n / 128` set for each number `n`
+fn bitset(numbers: &[u8]) -> [u128; 2] {
+    let mut bits = [0; 2];
+    for &n in numbers {
+        bits[n as usize / 128] |= 1 << (n % 128);
+    }
+    bits
+}
+
+impl Card {
+    /// The winning numbers as a bitset, see [`bitset`]
+    pub fn winning_bitset(&self) -> [u128; 2] {
+        bitset(&self.winning_numbers)
+    }
+
+    /// The number of our numbers that are also winning numbers
+    pub fn matches(&self) -> usize {
+        let winning = self.winning_bitset();
+        let ours = bitset(&self.our_numbers);
+        winning
+            .iter()
+            .zip(&ours)
+            .map(|(w, o)| (w & o).count_ones() as usize)
+            .sum()
+    }
+}
+
 pub fn parse(input: &str) -> Vec<Card> {
     input
         .lines()
@@ -47,12 +75,7 @@ pub fn solve_part_1(input: &[Card]) -> u32 {
     let mut sum = 0;
 
     for card in input {
-        // Count the number of winning numbers that are in our numbers
-        let num_winning = card
-            .winning_numbers
-            .iter()
-            .filter(|n| card.our_numbers.contains(n))
-            .count();
+        let num_winning = card.matches();
         if num_winning > 0 {
             sum += 1 << (num_winning as u32 - 1);
         }
@@ -61,15 +84,11 @@ pub fn solve_part_1(input: &[Card]) -> u32 {
     sum
 }
 
-pub fn solve_part_2(input: &[Card]) -> u32 {
-    let mut card_counts = vec![1; input.len()];
+pub fn solve_part_2(input: &[Card]) -> u64 {
+    let mut card_counts = vec![1u64; input.len()];
 
     for i in 0..input.len() {
-        let num_winning = input[i]
-            .winning_numbers
-            .iter()
-            .filter(|n| input[i].our_numbers.contains(n))
-            .count();
+        let num_winning = input[i].matches();
 
         for x in 0..num_winning {
             let x = i + x + 1;
@@ -104,4 +123,45 @@ Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
         let input = parse(TEST_INPUT);
         assert_eq!(solve_part_2(&input), 30);
     }
+
+    #[test]
+    fn test_matches() {
+        let input = parse(TEST_INPUT);
+        let matches = input.iter().map(Card::matches).collect::<Vec<_>>();
+        assert_eq!(matches, vec![4, 2, 2, 1, 0, 0]);
+    }
+
+    #[test]
+    fn test_matches_naive() {
+        let input = parse(TEST_INPUT);
+        for card in &input {
+            let naive = card
+                .our_numbers
+                .iter()
+                .filter(|n| card.winning_numbers.contains(n))
+                .count();
+            assert_eq!(card.matches(), naive);
+        }
+
+        let card: Card = "Card 1: 0 127 128 255 | 255 1 128 2 0".parse().unwrap();
+        assert_eq!(card.winning_bitset(), [1 | 1 << 127, 1 | 1 << 127]);
+        assert_eq!(card.matches(), 3);
+    }
+
+    #[test]
+    fn test_part_2_large_counts() {
+        // Each card wins a copy of every card after it, so the number of each
+        // card doubles down the list
+        let input = (0..34)
+            .map(|i| {
+                let numbers = (1..34 - i).map(|n| n.to_string()).collect::<Vec<_>>();
+                let numbers = numbers.join(" ");
+                format!("Card {}: {numbers} | {numbers}", i + 1)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let input = parse(&input);
+        assert_eq!(solve_part_2(&input), (1 << 34) - 1);
+    }
 }
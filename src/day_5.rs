@@ -60,6 +60,30 @@ struct Mapping {
 }
 
 impl Mapping {
+    /// Check that the chunks are sorted by source_start and don't overlap,
+    /// which the queries on the mapping rely on
+    fn validate(&self) -> Result<(), String> {
+        for chunk in &self.chunks {
+            if chunk.source_end < chunk.source_start {
+                return Err(format!("chunk {chunk:?} is empty"));
+            }
+        }
+
+        for pair in self.chunks.windows(2) {
+            if pair[1].source_start < pair[0].source_start {
+                return Err(format!(
+                    "chunks {:?} and {:?} are unsorted",
+                    pair[0], pair[1]
+                ));
+            }
+            if pair[1].source_start <= pair[0].source_end {
+                return Err(format!("chunks {:?} and {:?} overlap", pair[0], pair[1]));
+            }
+        }
+
+        Ok(())
+    }
+
     fn query_point(&self, source_id: i64) -> i64 {
         for chunk in &self.chunks {
             if source_id >= chunk.source_start && source_id <= chunk.source_end {
@@ -90,6 +114,64 @@ impl Mapping {
             chunk_idx,
         }
     }
+
+    /// Map a set of source ranges to the set of destination ranges they cover,
+    /// splitting them wherever they straddle the edge of a chunk
+    fn apply_ranges(&self, ranges: &[RangeInclusive<i64>]) -> Vec<RangeInclusive<i64>> {
+        ranges
+            .iter()
+            .flat_map(|range| self.query_range(range.clone()))
+            .map(|chunk| chunk.dest_range())
+            .collect()
+    }
+
+    /// A single mapping equivalent to applying this mapping, then `next`
+    fn compose(&self, next: &Mapping) -> Mapping {
+        // Outside of every chunk of both mappings, both are the identity
+        let ends = self.chunks.iter().chain(&next.chunks);
+        let (Some(start), Some(end)) = (
+            ends.clone().map(|chunk| chunk.source_start).min(),
+            ends.map(|chunk| chunk.source_end).max(),
+        ) else {
+            return Mapping { chunks: Vec::new() };
+        };
+
+        // Split each piece of this mapping wherever its destination range
+        // straddles the edge of a chunk in the next mapping
+        let chunks = self
+            .query_range(start..=end)
+            .flat_map(|first| {
+                next.query_range(first.dest_range())
+                    .map(move |second| MappingChunk {
+                        source_start: second.source_start - first.offset,
+                        source_end: second.source_end - first.offset,
+                        offset: first.offset + second.offset,
+                    })
+            })
+            .filter(|chunk| chunk.offset != 0)
+            .collect();
+
+        Mapping { chunks }
+    }
+
+    /// The mapping from space B back to space A
+    ///
+    /// Only a true inverse if no two source IDs map to the same destination
+    /// ID, otherwise some IDs map back to a source ID that doesn't map to them.
+    fn invert(&self) -> Mapping {
+        let mut chunks = self
+            .chunks
+            .iter()
+            .map(|chunk| MappingChunk {
+                source_start: chunk.source_start + chunk.offset,
+                source_end: chunk.source_end + chunk.offset,
+                offset: -chunk.offset,
+            })
+            .collect::<Vec<_>>();
+        chunks.sort_by_key(|chunk| chunk.source_start);
+
+        Mapping { chunks }
+    }
 }
 
 #[derive(Debug)]
@@ -129,8 +211,9 @@ impl<'a> Iterator for RangeQueryIter<'a> {
         let source_end;
         let offset;
         if map_chunk.source_start > self.source_start {
-            // The prefix before the next mapping chunk
-            source_end = map_chunk.source_start - 1;
+            // The prefix before the next mapping chunk, or the whole of the
+            // remaining range if it ends before that chunk starts
+            source_end = (map_chunk.source_start - 1).min(self.source_end);
             offset = 0;
         } else {
             // The bit of the next mapping chunk covered by the requested source range
@@ -162,6 +245,29 @@ impl AsRef<Input> for Input {
     }
 }
 
+fn parse_mappings<'a>(lines: impl Iterator<Item = &'a str>) -> Vec<Mapping> {
+    let mut mappings = Vec::new();
+
+    for line in lines {
+        if line.ends_with("map:") {
+            mappings.push(Mapping { chunks: Vec::new() });
+        } else {
+            let chunk = line.parse().unwrap();
+            mappings.last_mut().unwrap().chunks.push(chunk);
+        }
+    }
+
+    // Ensure all the mappings are correctly sorted
+    for mapping in &mut mappings {
+        mapping.chunks.sort_by_key(|chunk| chunk.source_start);
+        if let Err(e) = mapping.validate() {
+            panic!("Invalid mapping: {e}");
+        }
+    }
+
+    mappings
+}
+
 pub fn parse(input: &str) -> Input {
     // Parses a string like:
     //
@@ -186,67 +292,105 @@ pub fn parse(input: &str) -> Input {
         .map(|s| s.parse().unwrap())
         .collect();
 
-    let mut mappings = Vec::new();
-
-    for line in lines {
-        if line.ends_with("map:") {
-            mappings.push(Mapping { chunks: Vec::new() });
-        } else {
-            let chunk = line.parse().unwrap();
-            mappings.last_mut().unwrap().chunks.push(chunk);
-        }
-    }
-
-    // Ensure all the mappings are correctly sorted
-    for mapping in &mut mappings {
-        mapping.chunks.sort_by_key(|chunk| chunk.source_start);
-    }
-
     Input {
         source_ids,
-        mappings,
+        mappings: parse_mappings(lines),
     }
 }
 
-pub fn solve_part_1(input: &Input) -> i64 {
-    let mut min = i64::MAX;
+/// Parses the variant of the input where the seeds are given as inclusive
+/// ranges, eg "seeds: 79-92 55-67"
+///
+/// The ranges are stored as the same start/length pairs as the regular input.
+pub fn parse_with_range_seeds(input: &str) -> Input {
+    let mut lines = input.lines().filter(|line| !line.is_empty());
 
-    for id in &input.source_ids {
-        let mut id = *id;
-        for mapping in &input.mappings {
-            id = mapping.query_point(id);
-        }
+    let source_ids = lines
+        .next()
+        .unwrap()
+        .split_whitespace()
+        .skip(1)
+        .flat_map(|range| {
+            let (start, end) = range.split_once('-').unwrap();
+            let start: i64 = start.parse().unwrap();
+            let end: i64 = end.parse().unwrap();
+            [start, end - start + 1]
+        })
+        .collect();
 
-        min = min.min(id);
+    Input {
+        source_ids,
+        mappings: parse_mappings(lines),
     }
+}
 
-    min
+/// A single mapping equivalent to applying each of the mappings in turn
+fn compose_all(mappings: &[Mapping]) -> Mapping {
+    mappings
+        .iter()
+        .fold(Mapping { chunks: Vec::new() }, |acc, mapping| {
+            acc.compose(mapping)
+        })
 }
 
-pub fn solve_part_2(input: &Input) -> i64 {
-    fn min_dest(source_range: RangeInclusive<i64>, mappings: &[Mapping]) -> i64 {
-        match mappings {
-            [] => *source_range.start(),
-            [first, rest @ ..] => {
-                let mut min = i64::MAX;
-                for chunk in first.query_range(source_range) {
-                    let this_min = min_dest(chunk.dest_range(), rest);
-                    min = min.min(this_min);
-                }
-                min
-            }
-        }
-    }
+pub fn solve_part_1(input: &Input) -> i64 {
+    let mapping = compose_all(&input.mappings);
+
+    input
+        .source_ids
+        .iter()
+        .map(|&id| mapping.query_point(id))
+        .min()
+        .unwrap_or(i64::MAX)
+}
 
+/// The source IDs interpreted as pairs of range starts and lengths
+fn seed_ranges(input: &Input) -> Vec<RangeInclusive<i64>> {
     let starts = input.source_ids.iter().copied().step_by(2);
     let lens = input.source_ids.iter().copied().skip(1).step_by(2);
-    let mut min = i64::MAX;
-    for (start, len) in starts.zip(lens) {
-        let source_range = start..=(start + len - 1);
-        min = min.min(min_dest(source_range, &input.mappings));
+    starts
+        .zip(lens)
+        .map(|(start, len)| start..=(start + len - 1))
+        .collect()
+}
+
+pub fn solve_part_2(input: &Input) -> i64 {
+    let mut ranges = seed_ranges(input);
+
+    // Push the whole set of seed ranges through every layer of mappings
+    for mapping in &input.mappings {
+        ranges = mapping.apply_ranges(&ranges);
     }
 
-    min
+    ranges.iter().map(|range| *range.start()).min().unwrap()
+}
+
+/// Solves part 2 by walking up from location 0, mapping each location back
+/// through the inverted mappings until one lands in a seed range
+///
+/// Takes time proportional to the answer, so is much slower than
+/// [`solve_part_2`] on real inputs.
+pub fn solve_part_2_reverse(input: &Input) -> i64 {
+    let ranges = seed_ranges(input);
+    let inverted = input
+        .mappings
+        .iter()
+        .rev()
+        .map(Mapping::invert)
+        .collect::<Vec<_>>();
+
+    let map_all = |mappings: &[Mapping], id| mappings.iter().fold(id, |id, m| m.query_point(id));
+
+    (0..)
+        .find(|&location| {
+            let seed = map_all(&inverted, location);
+
+            // Check the seed really maps to this location, in case any of the
+            // mappings weren't invertible
+            ranges.iter().any(|range| range.contains(&seed))
+                && map_all(&input.mappings, seed) == location
+        })
+        .unwrap()
 }
 
 #[cfg(test)]
@@ -299,6 +443,116 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_mapping_apply_ranges() {
+        let input = parse(EXAMPLE_INPUT);
+        let seed_to_soil = &input.mappings[0];
+
+        let ranges = seed_to_soil.apply_ranges(&[79..=92, 55..=67]);
+        assert_eq!(ranges, vec![81..=94, 57..=69]);
+
+        // A range straddling the edge of a chunk gets split in two
+        let ranges = seed_to_soil.apply_ranges(&[45..=55, 96..=99]);
+        assert_eq!(ranges, vec![45..=49, 52..=57, 98..=99, 50..=51]);
+
+        // A range ending before the first chunk isn't extended up to it
+        let ranges = seed_to_soil.apply_ranges(&[0..=4]);
+        assert_eq!(ranges, vec![0..=4]);
+    }
+
+    #[test]
+    fn test_mapping_validate() {
+        let input = parse(EXAMPLE_INPUT);
+        for mapping in &input.mappings {
+            assert_eq!(mapping.validate(), Ok(()));
+        }
+
+        let chunk = |source_start, source_end| MappingChunk {
+            source_start,
+            source_end,
+            offset: 1,
+        };
+
+        let overlapping = Mapping {
+            chunks: vec![chunk(0, 10), chunk(10, 20)],
+        };
+        let err = overlapping.validate().unwrap_err();
+        assert!(err.contains("overlap"), "{err}");
+
+        let unsorted = Mapping {
+            chunks: vec![chunk(11, 20), chunk(0, 10)],
+        };
+        let err = unsorted.validate().unwrap_err();
+        assert!(err.contains("unsorted"), "{err}");
+
+        // A zero length range in the input
+        let empty = Mapping {
+            chunks: vec![chunk(5, 4)],
+        };
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "overlap")]
+    fn test_parse_overlapping() {
+        parse(&EXAMPLE_INPUT.replace("50 98 2", "50 96 3"));
+    }
+
+    #[test]
+    fn test_mapping_compose() {
+        let input = parse(EXAMPLE_INPUT);
+        let seed_to_soil = &input.mappings[0];
+        let soil_to_fertilizer = &input.mappings[1];
+        let seed_to_fertilizer = seed_to_soil.compose(soil_to_fertilizer);
+
+        assert_eq!(seed_to_fertilizer.query_point(79), 81);
+        assert_eq!(seed_to_fertilizer.query_point(14), 53);
+        assert_eq!(seed_to_fertilizer.query_point(55), 57);
+        assert_eq!(seed_to_fertilizer.query_point(13), 52);
+
+        for id in -10..120 {
+            assert_eq!(
+                seed_to_fertilizer.query_point(id),
+                soil_to_fertilizer.query_point(seed_to_soil.query_point(id))
+            );
+        }
+
+        // Composing the whole chain maps seeds straight to locations
+        let seed_to_location = compose_all(&input.mappings);
+        for id in -10..120 {
+            let location = input.mappings.iter().fold(id, |id, m| m.query_point(id));
+            assert_eq!(seed_to_location.query_point(id), location);
+        }
+    }
+
+    #[test]
+    fn test_mapping_invert() {
+        let input = parse(EXAMPLE_INPUT);
+        for mapping in &input.mappings {
+            let inverse = mapping.invert();
+            for id in 0..110 {
+                assert_eq!(inverse.query_point(mapping.query_point(id)), id);
+            }
+        }
+
+        let seed_to_soil = input.mappings[0].invert();
+        assert_eq!(
+            seed_to_soil.chunks,
+            vec![
+                MappingChunk {
+                    source_start: 50,
+                    source_end: 51,
+                    offset: 48,
+                },
+                MappingChunk {
+                    source_start: 52,
+                    source_end: 99,
+                    offset: -2,
+                },
+            ]
+        );
+    }
+
     const EXAMPLE_INPUT: &str = "seeds: 79 14 55 13
 
 seed-to-soil map:
@@ -346,4 +600,28 @@ humidity-to-location map:
         let ans = solve_part_2(&input);
         assert_eq!(ans, 46)
     }
+
+    #[test]
+    fn test_part_2_reverse() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_2_reverse(&input), solve_part_2(&input));
+
+        let input = parse(&EXAMPLE_INPUT.replace("seeds: 79 14 55 13", "seeds: 90 3 0 5"));
+        assert_eq!(solve_part_2_reverse(&input), solve_part_2(&input));
+    }
+
+    #[test]
+    fn test_range_seeds() {
+        let ranged = EXAMPLE_INPUT.replace("seeds: 79 14 55 13", "seeds: 79-92 55-67");
+
+        let input = parse_with_range_seeds(&ranged);
+        assert_eq!(input.source_ids, vec![79, 14, 55, 13]);
+        assert_eq!(solve_part_2(&input), 46);
+    }
+
+    #[test]
+    fn test_parse_negative_seeds() {
+        let input = parse(&EXAMPLE_INPUT.replace("seeds: 79 14 55 13", "seeds: -5 3 7"));
+        assert_eq!(input.source_ids, vec![-5, 3, 7]);
+    }
 }
@@ -1,3 +1,5 @@
+use std::ops::RangeInclusive;
+
 #[derive(Debug)]
 pub struct Race {
     time: u64,
@@ -5,28 +7,37 @@ pub struct Race {
 }
 
 impl Race {
-    fn ways_to_win(self) -> u64 {
-        // The numbers are small enough that we should be able to get away with
-        // floating point math
+    /// The inclusive range of hold times that beat the record, or None if
+    /// none do
+    pub fn winning_hold_range(&self) -> Option<RangeInclusive<u64>> {
+        // Holding for `hold` travels `hold * (time - hold)`, which is symmetric
+        // about time / 2 and increases up to it. Tying the record isn't a win.
+        let beats = |hold: u64| hold as u128 * (self.time - hold) as u128 > self.distance as u128;
 
-        // time = hold + distance/hold
-        // => beats the record when:
-        //  hold + distance / hold - record < 0
-        //  hold^2 - hold * record + distance < 0
-        //
-        // Has roots: hold = (record +- sqrt(record^2 - 4 * distance)) / 2
+        let half = self.time / 2;
+        if !beats(half) {
+            return None;
+        }
 
-        let record = self.time as f64;
-        let distance = self.distance as f64;
+        // Binary search for the first winning hold time, keeping `lo` losing
+        // and `hi` winning. Holding for 0 never moves, so never wins.
+        let (mut lo, mut hi) = (0, half);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if beats(mid) {
+                hi = mid;
+            } else {
+                lo = mid;
+            }
+        }
 
-        let s = (record * record - 4.0 * distance).sqrt();
-        let r1 = (record - s) / 2.0;
-        let r2 = (record + s) / 2.0;
-
-        let r1 = r1.ceil() as u64;
-        let r2 = r2.floor() as u64;
+        // The winning hold times are symmetric about time / 2
+        Some(hi..=self.time - hi)
+    }
 
-        r2 - r1 + 1
+    fn ways_to_win(self) -> u64 {
+        self.winning_hold_range()
+            .map_or(0, |range| range.end() - range.start() + 1)
     }
 }
 
@@ -77,3 +88,68 @@ pub fn solve_part_2(input: &str) -> u64 {
 
     Race { time, distance }.ways_to_win()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "Time:      7  15   30
+Distance:  9  40  200";
+
+    fn ways_to_win(time: u64, distance: u64) -> u64 {
+        Race { time, distance }.ways_to_win()
+    }
+
+    #[test]
+    fn test_ways_to_win() {
+        assert_eq!(ways_to_win(7, 9), 4);
+        assert_eq!(ways_to_win(15, 40), 8);
+
+        // The roots are exactly 10 and 20 here, which only tie the record
+        assert_eq!(ways_to_win(30, 200), 9);
+    }
+
+    #[test]
+    fn test_ways_to_win_degenerate() {
+        assert_eq!(ways_to_win(0, 0), 0);
+        assert_eq!(ways_to_win(1, 0), 0);
+        assert_eq!(ways_to_win(2, 0), 1);
+        assert_eq!(ways_to_win(5, 0), 4);
+        assert_eq!(ways_to_win(4, 100), 0);
+    }
+
+    #[test]
+    fn test_ways_to_win_exact_roots() {
+        // Holding for 3 or 7 exactly ties the record
+        assert_eq!(ways_to_win(10, 21), 3);
+
+        // Holding for 5 exactly ties the record, and nothing beats it
+        assert_eq!(ways_to_win(10, 25), 0);
+        assert_eq!(ways_to_win(10, 24), 1);
+
+        // Too large for the roots to be found exactly with f64s
+        let (a, b) = (123_456_789, 987_654_321);
+        assert_eq!(ways_to_win(a + b, a * b), b - a - 1);
+        assert_eq!(ways_to_win(a + b, a * b - 1), b - a + 1);
+    }
+
+    #[test]
+    fn test_winning_hold_range() {
+        let range = |time, distance| Race { time, distance }.winning_hold_range();
+        assert_eq!(range(7, 9), Some(2..=5));
+        assert_eq!(range(15, 40), Some(4..=11));
+        assert_eq!(range(30, 200), Some(11..=19));
+        assert_eq!(range(71530, 940200), Some(14..=71516));
+        assert_eq!(range(10, 25), None);
+    }
+
+    #[test]
+    fn test_part_1() {
+        assert_eq!(solve_part_1(EXAMPLE_INPUT), 288);
+    }
+
+    #[test]
+    fn test_part_2() {
+        assert_eq!(solve_part_2(EXAMPLE_INPUT), 71503);
+    }
+}
@@ -1,5 +1,7 @@
 use std::cmp::Reverse;
 
+use anyhow::{anyhow, bail};
+
 #[derive(Clone, Copy, Debug)]
 #[repr(u8)]
 pub enum Card {
@@ -64,6 +66,43 @@ impl Card {
     }
 }
 
+/// The rules for scoring hands against each other
+pub trait Ruleset {
+    /// The value of the card when breaking ties between hands of the same
+    /// pattern, higher values being stronger
+    fn card_value(&self, card: Card) -> u8;
+
+    /// Whether J is a joker, counting as whichever card makes the strongest
+    /// pattern
+    fn uses_jokers(&self) -> bool;
+}
+
+/// The rules from part 1, where J is a jack
+pub struct Jacks;
+
+impl Ruleset for Jacks {
+    fn card_value(&self, card: Card) -> u8 {
+        card.value_with_jacks()
+    }
+
+    fn uses_jokers(&self) -> bool {
+        false
+    }
+}
+
+/// The rules from part 2, where J is a joker and the weakest card
+pub struct Jokers;
+
+impl Ruleset for Jokers {
+    fn card_value(&self, card: Card) -> u8 {
+        card.value_with_jokers()
+    }
+
+    fn uses_jokers(&self) -> bool {
+        true
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Pattern {
     FiveOfAKind,
@@ -75,9 +114,9 @@ pub enum Pattern {
     HighCard,
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct Hand {
-    cards: [Card; 5],
+    cards: Vec<Card>,
     bid: u32,
 }
 
@@ -90,58 +129,109 @@ pub fn parse(input: &str) -> Vec<Hand> {
     input
         .lines()
         .map(|line| {
-            let (hand, bid) = line.split_at(5);
-            let cards =
-                std::array::from_fn(|i| Card::from_char(hand.chars().nth(i).unwrap()).unwrap());
+            let (hand, bid) = line.split_once(' ').unwrap();
             let bid = bid.trim().parse().unwrap();
-            Hand { cards, bid }
+            Hand::new(hand, bid).unwrap()
         })
         .collect()
 }
 
-fn find_pattern(hand: &[Card; 5], use_jokers: bool) -> Pattern {
+impl Hand {
+    /// A hand from a string of cards, eg "32T3K"
+    pub fn new(cards_str: &str, bid: u32) -> anyhow::Result<Hand> {
+        let cards = cards_str
+            .chars()
+            .map(|c| Card::from_char(c).ok_or_else(|| anyhow!("Invalid card {c:?}")))
+            .collect::<Result<Vec<_>, _>>()?;
+        if cards.is_empty() {
+            bail!("Expected at least one card");
+        }
+
+        Ok(Hand { cards, bid })
+    }
+
+    /// The kind of poker hand this is, with any jokers counting as whichever
+    /// card makes the strongest hand if `use_jokers` is set
+    pub fn pattern(&self, use_jokers: bool) -> Pattern {
+        if use_jokers {
+            find_pattern(&self.counts_with_jokers())
+        } else {
+            find_pattern(&self.counts())
+        }
+    }
+
+    /// How many times each distinct card appears in the hand, sorted from most
+    /// to least common and padded with zeroes to the length of the hand, eg a
+    /// full house is `[3, 2, 0, 0, 0]`
+    pub fn counts(&self) -> Vec<u8> {
+        sorted_counts(&self.cards, false)
+    }
+
+    /// As [`Hand::counts`], but with any jokers counted as the most common card
+    fn counts_with_jokers(&self) -> Vec<u8> {
+        sorted_counts(&self.cards, true)
+    }
+}
+
+fn sorted_counts(hand: &[Card], use_jokers: bool) -> Vec<u8> {
     // Count how the occurrences of each card, and sort by count
     let mut counts = [0; 13];
     for card in hand {
         counts[*card as usize] += 1;
     }
 
-    if use_jokers {
-        let joker_count = counts[Card::JokerJack as usize];
-        counts[Card::JokerJack as usize] = 0;
-        counts.sort();
-        counts[12] += joker_count;
+    let joker_count = if use_jokers {
+        std::mem::take(&mut counts[Card::JokerJack as usize])
     } else {
-        counts.sort()
-    }
+        0
+    };
+
+    counts.sort_by_key(|&count| Reverse(count));
+    counts[0] += joker_count;
 
-    match &counts[10..] {
-        [0, 0, 5] => Pattern::FiveOfAKind,
-        [0, 1, 4] => Pattern::FourOfAKind,
-        [0, 2, 3] => Pattern::FullHouse,
-        [1, 1, 3] => Pattern::ThreeOfAKind,
-        [1, 2, 2] => Pattern::TwoPair,
-        [1, 1, 2] => Pattern::OnePair,
+    counts
+        .into_iter()
+        .chain(std::iter::repeat(0))
+        .take(hand.len())
+        .collect()
+}
+
+/// The pattern made by the sorted card counts of a hand of any length, where
+/// larger groups of cards than the pattern needs still make that pattern
+fn find_pattern(counts: &[u8]) -> Pattern {
+    match *counts {
+        [n, ..] if n >= 5 => Pattern::FiveOfAKind,
+        [4, ..] => Pattern::FourOfAKind,
+        [3, n, ..] if n >= 2 => Pattern::FullHouse,
+        [3, ..] => Pattern::ThreeOfAKind,
+        [2, 2, ..] => Pattern::TwoPair,
+        [2, ..] => Pattern::OnePair,
         _ => Pattern::HighCard,
     }
 }
 
-fn sorting_key(hand: &Hand, use_jokers: bool) -> impl Ord + Copy + Clone {
-    let pattern = find_pattern(&hand.cards, use_jokers);
+fn sorting_key(hand: &Hand, ruleset: &impl Ruleset) -> impl Ord {
+    let pattern = hand.pattern(ruleset.uses_jokers());
 
     // Use reverse so that higher card values come before lower ones when sorting
-    let values = if use_jokers {
-        hand.cards.map(|card| Reverse(card.value_with_jokers()))
-    } else {
-        hand.cards.map(|card| Reverse(card.value_with_jacks()))
-    };
+    let values = hand
+        .cards
+        .iter()
+        .map(|&card| Reverse(ruleset.card_value(card)))
+        .collect::<Vec<_>>();
 
     (pattern, values)
 }
 
-fn total_winnings(hands: &[Hand], use_jokers: bool) -> u32 {
+/// The sum of each hand's bid multiplied by its rank, where the weakest hand has
+/// rank 1.
+///
+/// The sort is stable, so hands of identical strength keep their input order in
+/// the strongest-first ordering. After reversing, the later of two tied hands
+/// in the input therefore takes the lower rank.
+pub fn total_winnings(hands: &[Hand], ruleset: &impl Ruleset) -> u32 {
     let mut hands = hands.to_vec();
-    hands.sort_by_cached_key(|hand| sorting_key(hand, use_jokers));
+    hands.sort_by_cached_key(|hand| sorting_key(hand, ruleset));
 
     hands
         .iter()
@@ -155,9 +245,153 @@ fn total_winnings(hands: &[Hand], use_jokers: bool) -> u32 {
 }
 
 pub fn solve_part_1(input: &[Hand]) -> u32 {
-    total_winnings(input, false)
+    total_winnings(input, &Jacks)
 }
 
 pub fn solve_part_2(input: &[Hand]) -> u32 {
-    total_winnings(input, true)
+    total_winnings(input, &Jokers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
+
+    #[test]
+    fn test_part_1() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_1(&input), 6440);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_2(&input), 5905);
+    }
+
+    /// J is a jack, and the strongest card
+    struct HighJacks;
+
+    impl Ruleset for HighJacks {
+        fn card_value(&self, card: Card) -> u8 {
+            match card {
+                Card::JokerJack => 15,
+                _ => card.value_with_jacks(),
+            }
+        }
+
+        fn uses_jokers(&self) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn test_rulesets() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(total_winnings(&input, &Jacks), solve_part_1(&input));
+        assert_eq!(total_winnings(&input, &Jokers), solve_part_2(&input));
+
+        // The bids of each hand
+        const JJ: u32 = 1;
+        const AA: u32 = 2;
+        const QJ: u32 = 4;
+        let input = parse(
+            "JJ234 1
+AA234 2
+QJ234 4",
+        );
+
+        // Two one pair hands above a high card hand, with the aces breaking the tie
+        assert_eq!(total_winnings(&input, &Jacks), QJ + JJ * 2 + AA * 3);
+
+        // The jokers make three of a kind
+        assert_eq!(total_winnings(&input, &Jokers), QJ + AA * 2 + JJ * 3);
+
+        // The pair of jacks now beats the pair of aces
+        assert_eq!(total_winnings(&input, &HighJacks), QJ + AA * 2 + JJ * 3);
+    }
+
+    #[test]
+    fn test_tied_hands_keep_input_order() {
+        let input = parse(
+            "KK677 10
+32T3K 1
+KK677 20",
+        );
+
+        // 32T3K is the weakest with rank 1, then the two tied KK677 hands take
+        // ranks 2 and 3 in reverse input order
+        assert_eq!(solve_part_1(&input), 1 + 20 * 2 + 10 * 3);
+        assert_eq!(solve_part_2(&input), 1 + 20 * 2 + 10 * 3);
+    }
+
+    #[test]
+    fn test_pattern() {
+        let pattern = |cards| Hand::new(cards, 0).unwrap().pattern(false);
+        assert_eq!(pattern("AAAAA"), Pattern::FiveOfAKind);
+        assert_eq!(pattern("AA8AA"), Pattern::FourOfAKind);
+        assert_eq!(pattern("23332"), Pattern::FullHouse);
+        assert_eq!(pattern("TTT98"), Pattern::ThreeOfAKind);
+        assert_eq!(pattern("23432"), Pattern::TwoPair);
+        assert_eq!(pattern("A23A4"), Pattern::OnePair);
+        assert_eq!(pattern("23456"), Pattern::HighCard);
+    }
+
+    #[test]
+    fn test_pattern_with_jokers() {
+        let pattern = |cards, use_jokers| Hand::new(cards, 0).unwrap().pattern(use_jokers);
+
+        // Jokers upgrade a two pair
+        assert_eq!(pattern("KK22J", false), Pattern::TwoPair);
+        assert_eq!(pattern("KK22J", true), Pattern::FullHouse);
+        assert_eq!(pattern("KKJJ2", false), Pattern::TwoPair);
+        assert_eq!(pattern("KKJJ2", true), Pattern::FourOfAKind);
+
+        assert_eq!(pattern("2345J", true), Pattern::OnePair);
+        assert_eq!(pattern("JJJJJ", true), Pattern::FiveOfAKind);
+        assert_eq!(pattern("23456", true), Pattern::HighCard);
+    }
+
+    #[test]
+    fn test_hand_new() {
+        assert!(Hand::new("32T3K", 765).is_ok());
+        assert!(Hand::new("32T", 765).is_ok());
+        assert!(Hand::new("32T3KK2", 765).is_ok());
+        assert!(Hand::new("", 765).is_err());
+        assert!(Hand::new("32T3X", 765).is_err());
+    }
+
+    #[test]
+    fn test_pattern_other_lengths() {
+        let hand = |cards| Hand::new(cards, 0).unwrap();
+
+        assert_eq!(hand("KKK").counts(), [3, 0, 0]);
+        assert_eq!(hand("KKK").pattern(false), Pattern::ThreeOfAKind);
+        assert_eq!(hand("KK2").pattern(false), Pattern::OnePair);
+        assert_eq!(hand("K32").pattern(false), Pattern::HighCard);
+        assert_eq!(hand("KJ2").pattern(true), Pattern::OnePair);
+
+        assert_eq!(hand("KKKKKK2").pattern(false), Pattern::FiveOfAKind);
+        assert_eq!(hand("KKK2223").pattern(false), Pattern::FullHouse);
+        assert_eq!(hand("KKKK222").pattern(false), Pattern::FourOfAKind);
+        assert_eq!(hand("KK22334").pattern(false), Pattern::TwoPair);
+        assert_eq!(hand("KK22JJ4").pattern(true), Pattern::FourOfAKind);
+
+        // Hands of different lengths can be scored against each other
+        let input = parse("KKK 10\nKK234 1");
+        assert_eq!(solve_part_1(&input), 1 + 10 * 2);
+    }
+
+    #[test]
+    fn test_counts() {
+        let hands = parse("KK677 28\nQQQJA 483\nKTJJT 220");
+        assert_eq!(hands[0].counts(), [2, 2, 1, 0, 0]);
+        assert_eq!(hands[1].counts(), [3, 1, 1, 0, 0]);
+        assert_eq!(hands[2].counts_with_jokers(), [4, 1, 0, 0, 0]);
+    }
 }
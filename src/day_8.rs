@@ -140,11 +140,12 @@ pub fn solve_part_1(input: &Input) -> u64 {
     let mut node = input.map.get_node("AAA").unwrap();
     let zzz = input.map.get_node("ZZZ").unwrap();
 
+    // Check for the goal after every single instruction, as ZZZ may be reached
+    // part way through the instruction list
+    let mut instructions = input.instructions.iter().cycle();
     while node != zzz {
-        for dir in &input.instructions {
-            node = input.map.next_node(node, *dir);
-            steps += 1;
-        }
+        node = input.map.next_node(node, *instructions.next().unwrap());
+        steps += 1;
     }
 
     steps
@@ -163,7 +164,7 @@ pub fn solve_part_2(input: &Input) -> i64 {
     // before visiting a sink node for the first time, then visits a sink node
     // on a regular clock.
 
-    let mut preambles = Vec::new();
+    let mut preambles: Vec<i64> = Vec::new();
     let mut periods = Vec::new();
 
     for source_node in source_nodes {
@@ -193,11 +194,114 @@ pub fn solve_part_2(input: &Input) -> i64 {
         }
     }
 
-    // It turns out (at least in my input) that the preambles are all the same
-    // as the periods, such that the answer is just the plain lcm of the periods
-    // rather than anything clever with phase offsets
-    debug_assert!(preambles.iter().zip(periods.iter()).all(|(a, b)| a == b));
+    // Each ghost is on a sink node at every step count `preamble + k * period`
+    // for non-negative k. Find the first step count satisfying all of them.
+    let residues = preambles
+        .iter()
+        .zip(&periods)
+        .map(|(&preamble, &period)| (preamble, period))
+        .collect::<Vec<_>>();
+    let first = crate::util::crt(&residues).expect("Ghosts never align");
+
+    // The smallest solution to the congruences may come before some ghost has
+    // finished its preamble, so step forward whole common periods until every
+    // ghost has
+    let common_period = crate::util::lcm_iter(periods.iter().copied());
+    let latest_preamble = preambles.iter().copied().max().unwrap_or(0);
+    let behind = (latest_preamble - first).max(0);
+    first + (behind + common_period - 1) / common_period * common_period
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_1() {
+        let input = parse(
+            "RL
+
+AAA = (BBB, CCC)
+BBB = (DDD, EEE)
+CCC = (ZZZ, GGG)
+DDD = (DDD, DDD)
+EEE = (EEE, EEE)
+GGG = (GGG, GGG)
+ZZZ = (ZZZ, ZZZ)",
+        );
+        assert_eq!(solve_part_1(&input), 2);
+
+        let input = parse(
+            "LLR
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)",
+        );
+        assert_eq!(solve_part_1(&input), 6);
+    }
+
+    #[test]
+    fn test_part_1_mid_instructions() {
+        // ZZZ is reached after the second of three instructions
+        let input = parse(
+            "RLL
+
+AAA = (ZZZ, BBB)
+BBB = (ZZZ, AAA)
+ZZZ = (ZZZ, ZZZ)",
+        );
+        assert_eq!(solve_part_1(&input), 2);
+    }
+
+    #[test]
+    fn test_part_2_offset_preambles() {
+        // The first ghost hits PQZ after 2 steps then every 3, and the second
+        // hits RSZ after 1 step then every 2, so they first align at 5 rather
+        // than the lcm of the periods
+        let input = parse(
+            "L
+
+PQA = (PQB, PQB)
+PQB = (PQZ, PQZ)
+PQZ = (PQC, PQC)
+PQC = (PQD, PQD)
+PQD = (PQZ, PQZ)
+RSA = (RSZ, RSZ)
+RSZ = (RSB, RSB)
+RSB = (RSZ, RSZ)",
+        );
+        assert_eq!(solve_part_2(&input), 5);
+    }
 
-    // The common period
-    crate::util::lcm_iter(periods.iter().copied())
+    #[test]
+    fn test_part_2_long_preamble() {
+        // The first ghost hits BBZ after 4 steps then every 2, and the second
+        // hits CCZ after 1 step then every step. The smallest solution of the
+        // congruences is 0, before either ghost has arrived.
+        let input = parse(
+            "L
+
+BBA = (BB1, BB1)
+BB1 = (BB2, BB2)
+BB2 = (BB3, BB3)
+BB3 = (BBZ, BBZ)
+BBZ = (BB4, BB4)
+BB4 = (BBZ, BBZ)
+CCA = (CCZ, CCZ)
+CCZ = (CCZ, CCZ)",
+        );
+        assert_eq!(solve_part_2(&input), 4);
+    }
+
+    #[test]
+    fn test_part_2_no_ghosts() {
+        let input = parse(
+            "L
+
+BBB = (ZZZ, ZZZ)
+ZZZ = (ZZZ, ZZZ)",
+        );
+        assert_eq!(solve_part_2(&input), 0);
+    }
 }
@@ -1,35 +1,103 @@
 pub fn parse(input: &str) -> Vec<Vec<i64>> {
-    input
-        .lines()
-        .map(|line| {
-            line.split_whitespace()
-                .map(|num| num.parse().unwrap())
-                .collect()
-        })
-        .collect()
+    crate::util::parse_grid_of_ints(input)
 }
 
-fn extrapolate(values: impl ExactSizeIterator<Item = i64>) -> i64 {
-    let len = values.len() as i64;
-    let coefficients = (0..)
-        .map(|i| crate::util::binomial_coefficient(len, i) * (-1i64).pow((i + len + 1) as u32));
+/// The previous and next values in the sequence, or None if either doesn't
+/// fit in an i64.
+///
+/// The binomial coefficients in the expansion (and their products with the
+/// values) overflow an i64 for sequences of more than about 65 values, even when
+/// the extrapolated value itself is small, so the sums are accumulated in i128.
+fn checked_extrapolate_both(values: &[i64]) -> Option<(i64, i64)> {
+    let len = values.len() as i128;
 
-    values
-        .zip(coefficients)
-        .map(|(value, coef)| value * coef)
-        .sum::<i64>()
+    // The next value has a term of value * C(len, i) * (-1)^(i + len + 1) for
+    // each value, with the binomial coefficient built up incrementally.
+    // Extrapolating the reversed sequence gives each value a term of
+    // value * C(len, i + 1) * (-1)^i instead, which is the coefficient that
+    // the next value's term moves on to.
+    let mut coef = 1i128;
+    let mut prev = 0i128;
+    let mut next = 0i128;
+    for (i, &value) in (0..).zip(values) {
+        let value = value as i128;
+        let next_sign = if (i + len) % 2 == 0 { -1 } else { 1 };
+        let prev_sign = if i % 2 == 0 { 1 } else { -1 };
+
+        next = next.checked_add(value.checked_mul(coef * next_sign)?)?;
+        coef = coef.checked_mul(len - i)? / (i + 1);
+        prev = prev.checked_add(value.checked_mul(coef * prev_sign)?)?;
+    }
+
+    Some((i64::try_from(prev).ok()?, i64::try_from(next).ok()?))
+}
+
+/// The previous and next values in the sequence, in a single pass.
+///
+/// Panics if either doesn't fit in an i64.
+pub fn extrapolate_both(values: &[i64]) -> (i64, i64) {
+    checked_extrapolate_both(values).expect("Extrapolated value overflowed")
 }
 
 pub fn solve_part_1(input: &[Vec<i64>]) -> i64 {
-    input
-        .iter()
-        .map(|row| extrapolate(row.iter().copied()))
-        .sum()
+    input.iter().map(|row| extrapolate_both(row).1).sum()
 }
 
 pub fn solve_part_2(input: &[Vec<i64>]) -> i64 {
-    input
-        .iter()
-        .map(|row| extrapolate(row.iter().rev().copied()))
-        .sum()
+    input.iter().map(|row| extrapolate_both(row).0).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_INPUT: &str = "0 3 6 9 12 15
+1 3 6 10 15 21
+10 13 16 21 30 45";
+
+    #[test]
+    fn test_part_1() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_1(&input), 114);
+    }
+
+    #[test]
+    fn test_part_2() {
+        let input = parse(EXAMPLE_INPUT);
+        assert_eq!(solve_part_2(&input), 2);
+    }
+
+    #[test]
+    fn test_extrapolate_both() {
+        let input = parse(EXAMPLE_INPUT);
+        let both = input
+            .iter()
+            .map(|row| extrapolate_both(row))
+            .collect::<Vec<_>>();
+        assert_eq!(both, vec![(-3, 18), (0, 28), (5, 68)]);
+
+        let prev_sum = both.iter().map(|(prev, _)| prev).sum::<i64>();
+        let next_sum = both.iter().map(|(_, next)| next).sum::<i64>();
+        assert_eq!(prev_sum, solve_part_2(&input));
+        assert_eq!(next_sum, solve_part_1(&input));
+
+        let values = (0..80).map(|i| 1000 * i + 7).collect::<Vec<_>>();
+        assert_eq!(extrapolate_both(&values), (-993, 80_007));
+
+        assert_eq!(extrapolate_both(&[]), (0, 0));
+        assert_eq!(extrapolate_both(&[4]), (4, 4));
+    }
+
+    #[test]
+    fn test_extrapolate_long_sequence() {
+        // C(80, 40) alone is ~1e23, far beyond an i64, but the terms cancel
+        let values = (0..80).map(|i| 1000 * i + 7).collect::<Vec<_>>();
+        assert_eq!(checked_extrapolate_both(&values), Some((-993, 80_007)));
+    }
+
+    #[test]
+    fn test_extrapolate_overflow() {
+        let values = [0, i64::MAX];
+        assert_eq!(checked_extrapolate_both(&values), None);
+    }
 }
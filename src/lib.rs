@@ -206,7 +206,7 @@ pub fn print_results_table(results: &[RunResult]) {
 macro_rules! define_days {
     ($(($name:literal, $day_num:literal, $mod:ident)),* $(,)?) => {
         $(
-            mod $mod;
+            pub mod $mod;
         )*
 
         pub fn all_days() -> Vec<Box<dyn ErasedDay>> {
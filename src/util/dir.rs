@@ -9,6 +9,9 @@ pub enum Dir {
 }
 
 impl Dir {
+    /// The unit vector for this direction in screen coordinates, where y grows
+    /// downwards. This is the convention used by `Map2d`, where row 0 is the
+    /// top line of the input.
     pub fn to_vec2(self) -> Vec2 {
         match self {
             Dir::Up => Vec2 { x: 0, y: -1 },
@@ -18,6 +21,17 @@ impl Dir {
         }
     }
 
+    /// The unit vector for this direction in mathematical coordinates, where y
+    /// grows upwards. Identical to `to_vec2` other than the sign of y.
+    pub fn to_vec2_yup(self) -> Vec2 {
+        match self {
+            Dir::Up => Vec2 { x: 0, y: 1 },
+            Dir::Down => Vec2 { x: 0, y: -1 },
+            Dir::Left => Vec2 { x: -1, y: 0 },
+            Dir::Right => Vec2 { x: 1, y: 0 },
+        }
+    }
+
     pub fn opposite(self) -> Self {
         match self {
             Dir::Up => Dir::Down,
@@ -27,6 +41,9 @@ impl Dir {
         }
     }
 
+    /// Rotate 90 degrees anticlockwise, as seen on screen with y pointing down.
+    ///
+    /// Four consecutive calls always return the original direction.
     pub fn rotate_left(self) -> Self {
         match self {
             Dir::Up => Dir::Left,
@@ -36,6 +53,10 @@ impl Dir {
         }
     }
 
+    /// Rotate 90 degrees clockwise, as seen on screen with y pointing down.
+    ///
+    /// The inverse of `rotate_left`, so four consecutive calls always return
+    /// the original direction.
     pub fn rotate_right(self) -> Self {
         match self {
             Dir::Up => Dir::Right,
@@ -45,7 +66,48 @@ impl Dir {
         }
     }
 
+    /// The direction reached by making the given turn from this direction
+    pub fn turn(self, relative: Turn) -> Self {
+        match relative {
+            Turn::Straight => self,
+            Turn::Left => self.rotate_left(),
+            Turn::Right => self.rotate_right(),
+            Turn::Back => self.opposite(),
+        }
+    }
+
+    /// The turn that takes this direction to `other`
+    pub fn relative_to(self, other: Dir) -> Turn {
+        if other == self {
+            Turn::Straight
+        } else if other == self.rotate_left() {
+            Turn::Left
+        } else if other == self.rotate_right() {
+            Turn::Right
+        } else {
+            Turn::Back
+        }
+    }
+
+    /// The next direction in `CLOCKWISE` order
+    pub fn next_clockwise(self) -> Self {
+        let idx = Self::CLOCKWISE.iter().position(|&d| d == self).unwrap();
+        Self::CLOCKWISE[(idx + 1) % 4]
+    }
+
     pub const ALL: [Self; 4] = [Self::Up, Self::Down, Self::Left, Self::Right];
+
+    /// All directions in clockwise rotation order, starting from up
+    pub const CLOCKWISE: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+}
+
+/// A turn relative to some current direction
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Turn {
+    Straight,
+    Left,
+    Right,
+    Back,
 }
 
 impl std::ops::Add<Dir> for Vec2 {
@@ -55,3 +117,185 @@ impl std::ops::Add<Dir> for Vec2 {
         self + rhs.to_vec2()
     }
 }
+
+/// A direction including the diagonals, using the same y-down convention as `Dir`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dir8 {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Dir8 {
+    pub fn to_vec2(self) -> Vec2 {
+        match self {
+            Dir8::Up => Vec2 { x: 0, y: -1 },
+            Dir8::Down => Vec2 { x: 0, y: 1 },
+            Dir8::Left => Vec2 { x: -1, y: 0 },
+            Dir8::Right => Vec2 { x: 1, y: 0 },
+            Dir8::UpLeft => Vec2 { x: -1, y: -1 },
+            Dir8::UpRight => Vec2 { x: 1, y: -1 },
+            Dir8::DownLeft => Vec2 { x: -1, y: 1 },
+            Dir8::DownRight => Vec2 { x: 1, y: 1 },
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Dir8::Up => Dir8::Down,
+            Dir8::Down => Dir8::Up,
+            Dir8::Left => Dir8::Right,
+            Dir8::Right => Dir8::Left,
+            Dir8::UpLeft => Dir8::DownRight,
+            Dir8::UpRight => Dir8::DownLeft,
+            Dir8::DownLeft => Dir8::UpRight,
+            Dir8::DownRight => Dir8::UpLeft,
+        }
+    }
+
+    pub const ALL: [Self; 8] = [
+        Self::Up,
+        Self::Down,
+        Self::Left,
+        Self::Right,
+        Self::UpLeft,
+        Self::UpRight,
+        Self::DownLeft,
+        Self::DownRight,
+    ];
+}
+
+impl From<Dir> for Dir8 {
+    fn from(dir: Dir) -> Self {
+        match dir {
+            Dir::Up => Dir8::Up,
+            Dir::Down => Dir8::Down,
+            Dir::Left => Dir8::Left,
+            Dir::Right => Dir8::Right,
+        }
+    }
+}
+
+impl std::ops::Add<Dir8> for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: Dir8) -> Self::Output {
+        self + rhs.to_vec2()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_vec2_yup() {
+        for dir in Dir::ALL {
+            let down = dir.to_vec2();
+            let up = dir.to_vec2_yup();
+            assert_eq!(up, Vec2::new(down.x, -down.y));
+        }
+
+        assert_eq!(Dir::Up.to_vec2_yup(), Vec2::new(0, 1));
+        assert_eq!(Dir::Down.to_vec2_yup(), Vec2::new(0, -1));
+        assert_eq!(Dir::Left.to_vec2_yup(), Dir::Left.to_vec2());
+        assert_eq!(Dir::Right.to_vec2_yup(), Dir::Right.to_vec2());
+    }
+
+    #[test]
+    fn test_rotate_left() {
+        assert_eq!(Dir::Up.rotate_left(), Dir::Left);
+        assert_eq!(Dir::Left.rotate_left(), Dir::Down);
+        assert_eq!(Dir::Down.rotate_left(), Dir::Right);
+        assert_eq!(Dir::Right.rotate_left(), Dir::Up);
+
+        for dir in Dir::ALL {
+            // (x, y) -> (y, -x) is an anticlockwise rotation when y is down
+            let v = dir.to_vec2();
+            assert_eq!(dir.rotate_left().to_vec2(), Vec2::new(v.y, -v.x));
+
+            let rotated = dir.rotate_left().rotate_left().rotate_left().rotate_left();
+            assert_eq!(rotated, dir);
+        }
+    }
+
+    #[test]
+    fn test_rotate_right() {
+        assert_eq!(Dir::Up.rotate_right(), Dir::Right);
+        assert_eq!(Dir::Right.rotate_right(), Dir::Down);
+        assert_eq!(Dir::Down.rotate_right(), Dir::Left);
+        assert_eq!(Dir::Left.rotate_right(), Dir::Up);
+
+        for dir in Dir::ALL {
+            // (x, y) -> (-y, x) is a clockwise rotation when y is down
+            let v = dir.to_vec2();
+            assert_eq!(dir.rotate_right().to_vec2(), Vec2::new(-v.y, v.x));
+
+            let rotated = dir
+                .rotate_right()
+                .rotate_right()
+                .rotate_right()
+                .rotate_right();
+            assert_eq!(rotated, dir);
+            assert_eq!(dir.rotate_right().rotate_left(), dir);
+        }
+    }
+
+    #[test]
+    fn test_next_clockwise() {
+        for dir in Dir::ALL {
+            assert_eq!(dir.next_clockwise(), dir.rotate_right());
+
+            let rotated = dir
+                .next_clockwise()
+                .next_clockwise()
+                .next_clockwise()
+                .next_clockwise();
+            assert_eq!(rotated, dir);
+        }
+    }
+
+    #[test]
+    fn test_relative_turns() {
+        for dir in Dir::ALL {
+            for other in Dir::ALL {
+                assert_eq!(dir.turn(dir.relative_to(other)), other);
+            }
+        }
+
+        assert_eq!(Dir::Up.relative_to(Dir::Left), Turn::Left);
+        assert_eq!(Dir::Up.relative_to(Dir::Right), Turn::Right);
+        assert_eq!(Dir::Up.relative_to(Dir::Down), Turn::Back);
+        assert_eq!(Dir::Up.relative_to(Dir::Up), Turn::Straight);
+    }
+
+    #[test]
+    fn test_dir8_to_vec2() {
+        assert_eq!(Dir8::Up.to_vec2(), Vec2::new(0, -1));
+        assert_eq!(Dir8::Down.to_vec2(), Vec2::new(0, 1));
+        assert_eq!(Dir8::Left.to_vec2(), Vec2::new(-1, 0));
+        assert_eq!(Dir8::Right.to_vec2(), Vec2::new(1, 0));
+        assert_eq!(Dir8::UpLeft.to_vec2(), Vec2::new(-1, -1));
+        assert_eq!(Dir8::UpRight.to_vec2(), Vec2::new(1, -1));
+        assert_eq!(Dir8::DownLeft.to_vec2(), Vec2::new(-1, 1));
+        assert_eq!(Dir8::DownRight.to_vec2(), Vec2::new(1, 1));
+
+        for dir in Dir::ALL {
+            assert_eq!(Dir8::from(dir).to_vec2(), dir.to_vec2());
+        }
+    }
+
+    #[test]
+    fn test_dir8_opposite() {
+        for dir in Dir8::ALL {
+            assert_ne!(dir.opposite(), dir);
+            assert_eq!(dir.opposite().opposite(), dir);
+            assert_eq!(dir.to_vec2() + dir.opposite().to_vec2(), Vec2::zero());
+        }
+    }
+}
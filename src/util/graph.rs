@@ -1,8 +1,11 @@
 use std::cmp::{Ordering, Reverse};
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 use std::fmt::{Debug, Formatter};
 use std::hash::Hash;
 
+use super::{Map2d, Vec2};
+
 pub struct NodeAndCost<Node> {
     pub cost: i64,
     pub node: Node,
@@ -46,6 +49,17 @@ pub struct Path<Node: Debug> {
     pub nodes: Vec<Node>,
 }
 
+impl<Node: Debug + Copy> Path<Node> {
+    /// The goal node that terminated the search, along with the total cost of
+    /// reaching it
+    pub fn goal(&self) -> NodeAndCost<Node> {
+        NodeAndCost {
+            cost: self.cost,
+            node: *self.nodes.last().unwrap(),
+        }
+    }
+}
+
 pub fn dijkstra<Node, NodeIter>(
     start: Node,
     is_end: impl Fn(Node) -> bool,
@@ -119,3 +133,588 @@ where
         }
     })
 }
+
+/// As [`dijkstra`], but returning the optimal path as a plain list of nodes
+/// from `start` to the goal, along with its total cost
+pub fn dijkstra_path<Node, NodeIter>(
+    start: Node,
+    is_end: impl Fn(Node) -> bool,
+    next_nodes: impl Fn(Node) -> NodeIter,
+) -> Option<(Vec<Node>, i64)>
+where
+    Node: Copy + Eq + Hash + Debug,
+    NodeIter: Iterator<Item = NodeAndCost<Node>>,
+{
+    dijkstra(start, is_end, next_nodes).map(|path| (path.nodes, path.cost))
+}
+
+/// The cost of the cheapest path from `start` to `goal` in a graph given as a
+/// list of directed `(from, to, cost)` edges.
+///
+/// Undirected graphs must list each edge in both directions.
+pub fn dijkstra_edges<Node>(edges: &[(Node, Node, i64)], start: Node, goal: Node) -> Option<i64>
+where
+    Node: Copy + Eq + Hash + Debug,
+{
+    let mut adjacent: HashMap<Node, Vec<NodeAndCost<Node>>> = HashMap::new();
+    for &(from, to, cost) in edges {
+        adjacent
+            .entry(from)
+            .or_default()
+            .push(NodeAndCost { node: to, cost });
+    }
+
+    let next_nodes = |node: Node| {
+        adjacent
+            .get(&node)
+            .into_iter()
+            .flatten()
+            .map(|next| NodeAndCost {
+                node: next.node,
+                cost: next.cost,
+            })
+    };
+
+    dijkstra(start, |node| node == goal, next_nodes).map(|path| path.cost)
+}
+
+/// The minimal cost of reaching every node reachable from `start`, including
+/// `start` itself at cost 0.
+pub fn dijkstra_all<Node, NodeIter>(
+    start: Node,
+    next_nodes: impl Fn(Node) -> NodeIter,
+) -> HashMap<Node, i64>
+where
+    Node: Copy + Eq + Hash,
+    NodeIter: Iterator<Item = NodeAndCost<Node>>,
+{
+    let mut costs = HashMap::new();
+    let mut queue = BinaryHeap::new();
+
+    // Wrap in a reverse as Rust's standard BinaryHeap is a max heap
+    queue.push(Reverse(CostOrder(NodeAndCost {
+        node: start,
+        cost: 0,
+    })));
+
+    while let Some(Reverse(CostOrder(NodeAndCost { node, cost }))) = queue.pop() {
+        if costs.contains_key(&node) {
+            continue;
+        }
+        costs.insert(node, cost);
+
+        for next in next_nodes(node) {
+            if !costs.contains_key(&next.node) {
+                queue.push(Reverse(CostOrder(NodeAndCost {
+                    node: next.node,
+                    cost: cost + next.cost,
+                })));
+            }
+        }
+    }
+
+    costs
+}
+
+/// The minimal cost of reaching each node satisfying `is_goal` that is
+/// reachable from `start`.
+pub fn dijkstra_to_all_goals<Node, NodeIter>(
+    start: Node,
+    is_goal: impl Fn(Node) -> bool,
+    next_nodes: impl Fn(Node) -> NodeIter,
+) -> HashMap<Node, i64>
+where
+    Node: Copy + Eq + Hash,
+    NodeIter: Iterator<Item = NodeAndCost<Node>>,
+{
+    let mut costs = dijkstra_all(start, next_nodes);
+    costs.retain(|&node, _| is_goal(node));
+    costs
+}
+
+/// Breadth first search for the shortest path from `start` to any node
+/// satisfying `is_end`, in a graph where every edge has the same cost.
+///
+/// Returns the nodes of the path, including both `start` and the goal, along
+/// with the number of steps taken.
+pub fn bfs<Node, NodeIter>(
+    start: Node,
+    is_end: impl Fn(Node) -> bool,
+    next_nodes: impl Fn(Node) -> NodeIter,
+) -> Option<(Vec<Node>, usize)>
+where
+    Node: Copy + Eq + Hash,
+    NodeIter: Iterator<Item = Node>,
+{
+    // Maps each node seen so far to the node it was first reached from
+    let mut came_from: HashMap<Node, Option<Node>> = HashMap::from([(start, None)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        if is_end(node) {
+            let mut path = vec![node];
+            while let Some(prev_node) = came_from[path.last().unwrap()] {
+                path.push(prev_node);
+            }
+            path.reverse();
+
+            let steps = path.len() - 1;
+            return Some((path, steps));
+        }
+
+        for next_node in next_nodes(node) {
+            if let Entry::Vacant(entry) = came_from.entry(next_node) {
+                entry.insert(Some(node));
+                queue.push_back(next_node);
+            }
+        }
+    }
+
+    None
+}
+
+/// Which cells of `map` can be reached from `start` by orthogonal moves through
+/// passable tiles.
+///
+/// Every cell is unreachable if `start` is itself out of bounds or impassable.
+pub fn grid_bfs_reachable<Tile>(
+    map: &Map2d<Tile>,
+    start: Vec2,
+    passable: impl Fn(&Tile) -> bool,
+) -> Map2d<bool> {
+    let mut reachable = Map2d::new_default(map.size, false);
+    for pos in map.flood_fill(start, passable) {
+        reachable[pos] = true;
+    }
+    reachable
+}
+
+/// A disjoint set forest over the nodes `0..n`, with path compression and
+/// union by rank
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    component_count: usize,
+}
+
+impl UnionFind {
+    /// Each of the `n` nodes starts out in its own component
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+            component_count: n,
+        }
+    }
+
+    /// The representative node of the component containing `node`
+    pub fn find(&mut self, node: usize) -> usize {
+        let mut root = node;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        // Point everything on the way up directly at the root
+        let mut node = node;
+        while self.parent[node] != root {
+            let next = self.parent[node];
+            self.parent[node] = root;
+            node = next;
+        }
+
+        root
+    }
+
+    /// Merges the components containing `a` and `b`.
+    ///
+    /// Returns false if they were already in the same component.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let a = self.find(a);
+        let b = self.find(b);
+        if a == b {
+            return false;
+        }
+
+        let (parent, child) = if self.rank[a] >= self.rank[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parent[child] = parent;
+        if self.rank[parent] == self.rank[child] {
+            self.rank[parent] += 1;
+        }
+
+        self.component_count -= 1;
+        true
+    }
+
+    /// The number of distinct components remaining
+    pub fn component_count(&self) -> usize {
+        self.component_count
+    }
+}
+
+/// A label for each of the nodes `0..node_count`, such that two nodes have the
+/// same label iff they are connected by the undirected `edges`.
+///
+/// Labels are numbered from 0 in order of each component's lowest node.
+pub fn connected_components(node_count: usize, edges: &[(usize, usize)]) -> Vec<usize> {
+    let mut union_find = UnionFind::new(node_count);
+    for &(a, b) in edges {
+        union_find.union(a, b);
+    }
+
+    let mut root_labels = HashMap::new();
+    (0..node_count)
+        .map(|node| {
+            let root = union_find.find(node);
+            let next_label = root_labels.len();
+            *root_labels.entry(root).or_insert(next_label)
+        })
+        .collect()
+}
+
+/// Finds the cycle reachable from `start` in a functional graph, ie one where
+/// each node has at most one successor.
+///
+/// Returns the nodes making up the cycle, in the order they are visited and
+/// starting from the first node of the cycle that is reached, or None if the
+/// walk terminates before revisiting any node.
+pub fn find_cycle_in_graph<N>(start: N, next: impl Fn(&N) -> Option<N>) -> Option<Vec<N>>
+where
+    N: Hash + Eq + Clone,
+{
+    // Maps each node seen so far to its index in the path
+    let mut seen = HashMap::new();
+    let mut path = Vec::new();
+    let mut node = start;
+
+    loop {
+        if let Some(&cycle_start) = seen.get(&node) {
+            return Some(path.split_off(cycle_start));
+        }
+
+        seen.insert(node.clone(), path.len());
+        path.push(node.clone());
+        node = next(&node)?;
+    }
+}
+
+/// Returned by [`topo_sort`] when the graph contains a cycle
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleError {
+    /// A node lying on one of the cycles
+    pub node: usize,
+}
+
+impl std::fmt::Display for CycleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Graph contains a cycle through node {}", self.node)
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+/// Orders the nodes `0..node_count` such that for every directed edge
+/// `(from, to)`, `from` comes before `to`, using Kahn's algorithm.
+///
+/// Whenever several nodes are free to come next, the lowest is taken first,
+/// so the result is the lexicographically smallest valid order.
+pub fn topo_sort(node_count: usize, edges: &[(usize, usize)]) -> Result<Vec<usize>, CycleError> {
+    let mut successors = vec![Vec::new(); node_count];
+    let mut in_degree = vec![0; node_count];
+    for &(from, to) in edges {
+        successors[from].push(to);
+        in_degree[to] += 1;
+    }
+
+    let mut ready = (0..node_count)
+        .filter(|&node| in_degree[node] == 0)
+        .map(Reverse)
+        .collect::<BinaryHeap<_>>();
+    let mut order = Vec::with_capacity(node_count);
+    while let Some(Reverse(node)) = ready.pop() {
+        order.push(node);
+        for &next in &successors[node] {
+            in_degree[next] -= 1;
+            if in_degree[next] == 0 {
+                ready.push(Reverse(next));
+            }
+        }
+    }
+
+    if order.len() == node_count {
+        return Ok(order);
+    }
+
+    // Every node left over still has a predecessor that is also left over, so
+    // walking backwards through them from any one must eventually loop
+    let mut predecessor = vec![None; node_count];
+    for &(from, to) in edges {
+        if in_degree[from] > 0 && in_degree[to] > 0 {
+            predecessor[to] = Some(from);
+        }
+    }
+
+    let start = (0..node_count).find(|&node| in_degree[node] > 0).unwrap();
+    let cycle = find_cycle_in_graph(start, |&node| predecessor[node]).unwrap();
+    Err(CycleError { node: cycle[0] })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dijkstra_goal() {
+        // Nodes on a number line, where stepping forward by 1 costs 1 and
+        // jumping forward by 3 costs 2. Either 9 or 10 is a goal.
+        let path = dijkstra(
+            0i64,
+            |n| n == 9 || n == 10,
+            |n| {
+                [
+                    NodeAndCost {
+                        node: n + 1,
+                        cost: 1,
+                    },
+                    NodeAndCost {
+                        node: n + 3,
+                        cost: 2,
+                    },
+                ]
+                .into_iter()
+            },
+        )
+        .unwrap();
+
+        let goal = path.goal();
+        assert_eq!(goal.node, 9);
+        assert_eq!(goal.cost, 6);
+        assert_eq!(path.nodes, vec![0, 3, 6, 9]);
+    }
+
+    #[test]
+    fn test_dijkstra_path() {
+        //   A --1-- B --1-- D
+        //    \             /
+        //     5---- C ---1
+        let edges: &[(char, char, i64)] =
+            &[('A', 'B', 1), ('B', 'D', 1), ('A', 'C', 5), ('C', 'D', 1)];
+        let next = |n: char| {
+            edges
+                .iter()
+                .filter(move |(from, _, _)| *from == n)
+                .map(|&(_, node, cost)| NodeAndCost { node, cost })
+        };
+
+        assert_eq!(
+            dijkstra_path('A', |n| n == 'D', next),
+            Some((vec!['A', 'B', 'D'], 2))
+        );
+        assert_eq!(
+            dijkstra_path('A', |n| n == 'C', next),
+            Some((vec!['A', 'C'], 5))
+        );
+        assert_eq!(dijkstra_path('D', |n| n == 'A', next), None);
+    }
+
+    #[test]
+    fn test_dijkstra_edges() {
+        let edges = [
+            ("home", "shop", 4),
+            ("home", "park", 1),
+            ("park", "shop", 2),
+            ("shop", "work", 5),
+            ("park", "work", 10),
+            ("work", "home", 1),
+        ];
+
+        assert_eq!(dijkstra_edges(&edges, "home", "work"), Some(8));
+        assert_eq!(dijkstra_edges(&edges, "shop", "park"), Some(7));
+        assert_eq!(dijkstra_edges(&edges, "home", "home"), Some(0));
+        assert_eq!(dijkstra_edges(&edges, "home", "school"), None);
+    }
+
+    #[test]
+    fn test_dijkstra_all() {
+        //   A --1-- B --1-- D
+        //    \             /
+        //     5---- C ---1
+        //
+        //   E --1-- F
+        let edges: &[(char, char, i64)] = &[
+            ('A', 'B', 1),
+            ('B', 'D', 1),
+            ('A', 'C', 5),
+            ('D', 'C', 1),
+            ('E', 'F', 1),
+        ];
+        let next = |n: char| {
+            edges
+                .iter()
+                .filter(move |(from, _, _)| *from == n)
+                .map(|&(_, node, cost)| NodeAndCost { node, cost })
+        };
+
+        let costs = dijkstra_all('A', next);
+        assert_eq!(
+            costs,
+            HashMap::from([('A', 0), ('B', 1), ('C', 3), ('D', 2)])
+        );
+        assert!(!costs.contains_key(&'E'));
+        assert!(!costs.contains_key(&'F'));
+
+        assert_eq!(dijkstra_all('F', next), HashMap::from([('F', 0)]));
+    }
+
+    #[test]
+    fn test_dijkstra_to_all_goals() {
+        // Nodes on a number line, where stepping forward by 1 costs 3 and
+        // jumping forward by 4 costs 5. Every multiple of 5 up to 20 is a goal.
+        let next = |n: i64| {
+            [
+                NodeAndCost {
+                    node: n + 1,
+                    cost: 3,
+                },
+                NodeAndCost {
+                    node: n + 4,
+                    cost: 5,
+                },
+            ]
+            .into_iter()
+            .filter(|next| next.node <= 20)
+        };
+
+        let costs = dijkstra_to_all_goals(0, |n| n % 5 == 0 && n > 0, next);
+        assert_eq!(costs, HashMap::from([(5, 8), (10, 16), (15, 24), (20, 25)]));
+    }
+
+    #[test]
+    fn test_bfs() {
+        let map = Map2d::parse_grid(".....\n.###.\n...#.\n##.#.\n.....", |c| c);
+        let open_neighbors = |pos: Vec2| {
+            map.neighbors4(pos)
+                .filter(|&(_, tile)| tile == '.')
+                .map(|(next, _)| next)
+                .collect::<Vec<_>>()
+                .into_iter()
+        };
+        let start = Vec2::new(0, 2);
+        let goal = Vec2::new(2, 4);
+
+        let (path, steps) = bfs(start, |pos| pos == goal, open_neighbors).unwrap();
+        assert_eq!(steps, 4);
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&goal));
+
+        let unit_cost = dijkstra(
+            start,
+            |pos| pos == goal,
+            |pos| open_neighbors(pos).map(|node| NodeAndCost { node, cost: 1 }),
+        )
+        .unwrap();
+        assert_eq!(unit_cost.cost, steps as i64);
+
+        assert_eq!(
+            bfs(start, |pos| pos == Vec2::new(2, 1), open_neighbors),
+            None
+        );
+    }
+
+    #[test]
+    fn test_grid_bfs_reachable() {
+        let map = Map2d::parse_grid("..#..\n..#.#\n###..\n...#.\n.#.#.", |c| c);
+        let reachable = grid_bfs_reachable(&map, Vec2::new(0, 0), |&c| c == '.');
+
+        assert!(reachable[Vec2::new(1, 1)]);
+        assert!(!reachable[Vec2::new(2, 0)]);
+
+        // The top right pocket and the bottom left region are walled off
+        assert!(!reachable[Vec2::new(3, 0)]);
+        assert!(!reachable[Vec2::new(4, 4)]);
+        assert!(!reachable[Vec2::new(0, 4)]);
+        assert_eq!(reachable.count(|&r| r), 4);
+
+        let from_wall = grid_bfs_reachable(&map, Vec2::new(2, 0), |&c| c == '.');
+        assert_eq!(from_wall.count(|&r| r), 0);
+    }
+
+    #[test]
+    fn test_union_find() {
+        // Two disjoint triangles, 0-1-2 and 3-4-5
+        let mut union_find = UnionFind::new(6);
+        assert_eq!(union_find.component_count(), 6);
+
+        assert!(union_find.union(0, 1));
+        assert!(union_find.union(1, 2));
+        assert!(!union_find.union(2, 0));
+        assert!(union_find.union(3, 4));
+        assert!(union_find.union(5, 4));
+        assert!(!union_find.union(3, 5));
+
+        assert_eq!(union_find.component_count(), 2);
+        assert_eq!(union_find.find(0), union_find.find(2));
+        assert_eq!(union_find.find(3), union_find.find(5));
+        assert_ne!(union_find.find(1), union_find.find(4));
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let edges = [(0, 1), (1, 2), (2, 0), (5, 3), (3, 4), (4, 5)];
+        assert_eq!(connected_components(6, &edges), vec![0, 0, 0, 1, 1, 1]);
+
+        // An isolated node gets a component to itself
+        assert_eq!(connected_components(7, &edges)[6], 2);
+    }
+
+    #[test]
+    fn test_find_cycle_with_tail() {
+        // 0 -> 1 -> 2 -> 3 -> 4 -> 5 -> 2
+        let next = |n: &u32| Some(if *n == 5 { 2 } else { n + 1 });
+        assert_eq!(find_cycle_in_graph(0, next), Some(vec![2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn test_find_cycle_terminating() {
+        let next = |n: &u32| if *n < 5 { Some(n + 1) } else { None };
+        assert_eq!(find_cycle_in_graph(0, next), None);
+    }
+
+    #[test]
+    fn test_topo_sort() {
+        let edges = [(3, 1), (1, 0), (3, 2), (2, 0), (4, 2)];
+        let order = topo_sort(5, &edges).unwrap();
+        assert_eq!(order, vec![3, 1, 4, 2, 0]);
+
+        let position = |node| order.iter().position(|&n| n == node).unwrap();
+        for (from, to) in edges {
+            assert!(position(from) < position(to));
+        }
+    }
+
+    #[test]
+    fn test_topo_sort_lowest_first() {
+        // 0 becomes free once 1 is placed, and is lower than 2 which has been
+        // free all along
+        assert_eq!(topo_sort(3, &[(1, 0)]).unwrap(), vec![1, 0, 2]);
+
+        // With no edges at all, the nodes come out in order
+        assert_eq!(topo_sort(4, &[]).unwrap(), vec![0, 1, 2, 3]);
+
+        // A chain that has to be followed in reverse
+        let edges = [(3, 2), (2, 1), (1, 0)];
+        assert_eq!(topo_sort(4, &edges).unwrap(), vec![3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_topo_sort_cycle() {
+        // 0 -> 1 -> 2 -> 3 -> 1, with 3 -> 4 hanging off the cycle
+        let edges = [(0, 1), (1, 2), (2, 3), (3, 1), (3, 4)];
+        let err = topo_sort(5, &edges).unwrap_err();
+        assert!([1, 2, 3].contains(&err.node));
+    }
+}
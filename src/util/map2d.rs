@@ -1,12 +1,27 @@
-use super::{Dir, Vec2};
+use std::collections::HashMap;
 
+use super::{Dir, Dir8, Vec2};
+
+/// Common interface to a rectangular grid of tiles, implemented by [`Map2d`]
+/// and the views over it, so that algorithms can be written generically over
+/// any of them.
+///
+/// Positions run from `(0, 0)` in the top left corner to `size() - (1, 1)` in
+/// the bottom right.
 pub trait Map2dExt<Tile> {
+    /// The width and height of the grid
     fn size(&self) -> Vec2;
+
+    /// A copy of the tile at `pos`, or `None` if `pos` is out of bounds
     fn get(&self, pos: Vec2) -> Option<Tile>
     where
         Tile: Copy;
+
+    /// A mutable reference to the tile at `pos`, or `None` if `pos` is out of
+    /// bounds
     fn get_mut(&mut self, pos: Vec2) -> Option<&mut Tile>;
 
+    /// Print the grid to stdout, using `f` to render each tile
     fn debug_print(&self, f: impl Fn(Tile) -> char)
     where
         Tile: Copy,
@@ -22,7 +37,38 @@ pub trait Map2dExt<Tile> {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GridParseError {
+    /// The input has no rows at all
+    Empty,
+
+    /// A row's length differs from that of the first row
+    Jagged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for GridParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridParseError::Empty => write!(f, "Grid is empty"),
+            GridParseError::Jagged {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Grid row {row} has length {found}, expected length {expected}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GridParseError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Map2d<Tile> {
     pub size: Vec2,
     pub data: Vec<Tile>,
@@ -38,13 +84,77 @@ impl<Tile> Map2d<Tile> {
     }
 
     pub fn parse_grid(s: &str, f: impl Fn(char) -> Tile) -> Self {
-        let size_x = s.lines().next().unwrap().len();
-        let size_y = s.lines().count();
+        Self::try_parse_grid(s, f).expect("Invalid grid")
+    }
+
+    /// Parse a grid with one row per line, checking that every row has the
+    /// same length
+    pub fn try_parse_grid(s: &str, f: impl Fn(char) -> Tile) -> Result<Self, GridParseError> {
+        let size_x = s
+            .lines()
+            .next()
+            .ok_or(GridParseError::Empty)?
+            .chars()
+            .count();
+
+        let mut size_y = 0;
+        let mut data = Vec::new();
+        for (row, line) in s.lines().enumerate() {
+            let len = line.chars().count();
+            if len != size_x {
+                return Err(GridParseError::Jagged {
+                    row,
+                    expected: size_x,
+                    found: len,
+                });
+            }
+
+            data.extend(line.chars().map(&f));
+            size_y += 1;
+        }
+
+        let size = Vec2::new(size_x as i64, size_y);
+        Ok(Self { size, data })
+    }
+
+    /// Build a map from a list of rows, checking that every row has the same
+    /// length
+    pub fn from_rows(rows: Vec<Vec<Tile>>) -> Result<Self, GridParseError> {
+        let size_x = rows.first().map(|row| row.len()).unwrap_or(0);
+        let size_y = rows.len();
+
+        let mut data = Vec::with_capacity(size_x * size_y);
+        for (row_idx, row) in rows.into_iter().enumerate() {
+            if row.len() != size_x {
+                return Err(GridParseError::Jagged {
+                    row: row_idx,
+                    expected: size_x,
+                    found: row.len(),
+                });
+            }
+            data.extend(row);
+        }
+
         let size = Vec2::new(size_x as i64, size_y as i64);
+        Ok(Self { size, data })
+    }
 
-        let data = s.chars().filter(|&c| c != '\n').map(f).collect::<Vec<_>>();
+    /// Parse a grid as with `try_parse_grid`, additionally building an index
+    /// from each raw input character to every position it was seen at.
+    pub fn parse_indexed(
+        s: &str,
+        f: impl Fn(char) -> Tile,
+    ) -> Result<(Self, HashMap<char, Vec<Vec2>>), GridParseError> {
+        let raw = Map2d::try_parse_grid(s, |c| c)?;
 
-        Self { size, data }
+        let mut index = HashMap::<char, Vec<Vec2>>::new();
+        for (i, &c) in raw.data.iter().enumerate() {
+            index.entry(c).or_default().push(raw.pos_of(i));
+        }
+
+        let size = raw.size;
+        let data = raw.data.into_iter().map(f).collect();
+        Ok((Self { size, data }, index))
     }
 
     pub fn index_of(&self, pos: Vec2) -> Option<usize> {
@@ -67,9 +177,278 @@ impl<Tile> Map2d<Tile> {
         &self.data[start..=end]
     }
 
+    /// Overwrite the tile at `pos`, returning whether `pos` was in bounds
+    pub fn set(&mut self, pos: Vec2, tile: Tile) -> bool {
+        match self.index_of(pos) {
+            Some(i) => {
+                self.data[i] = tile;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// A copy of the tile at `pos`, with coordinates wrapped around the edges
+    /// of the map as if it tiled the plane
+    pub fn get_wrapping(&self, pos: Vec2) -> Tile
+    where
+        Tile: Copy,
+    {
+        let wrapped = Vec2::new(pos.x.rem_euclid(self.size.x), pos.y.rem_euclid(self.size.y));
+        self[wrapped]
+    }
+
+    /// Iterate the rows of the map from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[Tile]> + '_ {
+        self.data.chunks(self.size.x.max(1) as usize)
+    }
+
+    /// Iterate the tiles of column `x` from top to bottom, yielding nothing if
+    /// `x` is out of bounds
+    pub fn col_iter(&self, x: i64) -> impl Iterator<Item = Tile> + '_
+    where
+        Tile: Copy,
+    {
+        (0..self.size.y).map_while(move |y| self.get(Vec2::new(x, y)))
+    }
+
+    /// The tiles of column `x` from top to bottom, empty if `x` is out of bounds
+    pub fn get_col(&self, x: i64) -> Vec<Tile>
+    where
+        Tile: Copy,
+    {
+        self.col_iter(x).collect()
+    }
+
+    /// A map of the same size, with each tile converted by `f`
+    pub fn map<U>(&self, f: impl Fn(&Tile) -> U) -> Map2d<U> {
+        Map2d {
+            size: self.size,
+            data: self.data.iter().map(f).collect(),
+        }
+    }
+
+    /// Build a new map of the given size, taking each tile from the position in
+    /// this map given by `source_pos`
+    fn remapped(&self, size: Vec2, source_pos: impl Fn(Vec2) -> Vec2) -> Self
+    where
+        Tile: Clone,
+    {
+        let data = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| Vec2::new(x, y)))
+            .map(|pos| self.data[self.index_of(source_pos(pos)).unwrap()].clone())
+            .collect();
+
+        Self { size, data }
+    }
+
+    /// Swap the rows and columns of this map
+    pub fn transpose(&self) -> Self
+    where
+        Tile: Clone,
+    {
+        let size = Vec2::new(self.size.y, self.size.x);
+        self.remapped(size, |pos| Vec2::new(pos.y, pos.x))
+    }
+
+    /// Rotate this map a quarter turn clockwise
+    pub fn rotate_cw(&self) -> Self
+    where
+        Tile: Clone,
+    {
+        let size = Vec2::new(self.size.y, self.size.x);
+        self.remapped(size, |pos| Vec2::new(pos.y, self.size.y - pos.x - 1))
+    }
+
+    /// Rotate this map a quarter turn anticlockwise
+    pub fn rotate_ccw(&self) -> Self
+    where
+        Tile: Clone,
+    {
+        let size = Vec2::new(self.size.y, self.size.x);
+        self.remapped(size, |pos| Vec2::new(self.size.x - pos.y - 1, pos.x))
+    }
+
+    /// Mirror this map left to right
+    pub fn flip_horizontal(&self) -> Self
+    where
+        Tile: Clone,
+    {
+        self.remapped(self.size, |pos| Vec2::new(self.size.x - pos.x - 1, pos.y))
+    }
+
+    /// Mirror this map top to bottom
+    pub fn flip_vertical(&self) -> Self
+    where
+        Tile: Clone,
+    {
+        self.remapped(self.size, |pos| Vec2::new(pos.x, self.size.y - pos.y - 1))
+    }
+
+    /// Copy out the rectangle of the given size whose top left corner is at
+    /// `top_left`, or None if any of that rectangle is out of bounds
+    pub fn crop(&self, top_left: Vec2, size: Vec2) -> Option<Self>
+    where
+        Tile: Clone,
+    {
+        let bottom_right = top_left + size;
+        let in_bounds = top_left.x >= 0
+            && top_left.y >= 0
+            && size.x >= 0
+            && size.y >= 0
+            && bottom_right.x <= self.size.x
+            && bottom_right.y <= self.size.y;
+        if !in_bounds {
+            return None;
+        }
+
+        Some(self.remapped(size, |pos| pos + top_left))
+    }
+
+    /// A copy of this map enlarged by `thickness` tiles of `fill` on every
+    /// side. A position `pos` in this map is at `pos + (thickness, thickness)`
+    /// in the result.
+    pub fn with_border(&self, thickness: usize, fill: Tile) -> Self
+    where
+        Tile: Clone,
+    {
+        let offset = Vec2::new(thickness as i64, thickness as i64);
+        let mut bordered = Map2d::new_default(self.size + offset * 2, fill);
+        for (pos, tile) in self.iter() {
+            bordered[pos + offset] = tile.clone();
+        }
+        bordered
+    }
+
+    /// An owned copy of this map rotated such that its original 'up' direction
+    /// points towards `up`, using the same convention as `RotatedMap2d`
+    pub fn rotated_to(&self, up: Dir) -> Self
+    where
+        Tile: Copy,
+    {
+        let size = rotated_size(self.size, up);
+        self.remapped(size, |pos| rotated_source_pos(self.size, up, pos))
+    }
+
+    /// Iterate every tile along with its position, in row-major order
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2, &Tile)> + '_ {
+        self.data
+            .iter()
+            .enumerate()
+            .map(|(i, tile)| (self.pos_of(i), tile))
+    }
+
+    /// Mutably iterate every tile along with its position, in row-major order
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Vec2, &mut Tile)> + '_ {
+        let size_x = self.size.x;
+        self.data.iter_mut().enumerate().map(move |(i, tile)| {
+            let pos = Vec2::new(i as i64 % size_x, i as i64 / size_x);
+            (pos, tile)
+        })
+    }
+
     pub fn find(&self, predicate: impl Fn(&Tile) -> bool) -> Option<Vec2> {
         self.data.iter().position(predicate).map(|i| self.pos_of(i))
     }
+
+    /// The positions of every tile matching the predicate, in row-major order
+    pub fn find_all(&self, predicate: impl Fn(&Tile) -> bool) -> Vec<Vec2> {
+        self.iter()
+            .filter(|(_, tile)| predicate(tile))
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+
+    /// The number of tiles matching the predicate
+    pub fn count(&self, predicate: impl Fn(&Tile) -> bool) -> usize {
+        self.data.iter().filter(|tile| predicate(tile)).count()
+    }
+
+    /// Iterate the in-bounds orthogonal neighbours of `pos`, along with the
+    /// direction taken to reach each one and its tile
+    pub fn neighbors4_full(&self, pos: Vec2) -> impl Iterator<Item = (Dir, Vec2, Tile)> + '_
+    where
+        Tile: Copy,
+    {
+        Dir::ALL.into_iter().filter_map(move |dir| {
+            let neighbor = pos + dir;
+            self.get(neighbor).map(|tile| (dir, neighbor, tile))
+        })
+    }
+
+    /// Render the map with one line per row, converting each tile with `f`
+    pub fn to_string_with(&self, f: impl Fn(&Tile) -> char) -> String {
+        let mut s = String::with_capacity(self.data.len() + self.size.y as usize);
+        for y in 0..self.size.y {
+            if y > 0 {
+                s.push('\n');
+            }
+            s.extend(self.get_row(y).iter().map(&f));
+        }
+        s
+    }
+
+    /// Iterate the in-bounds orthogonal neighbours of `pos` and their tiles
+    pub fn neighbors4(&self, pos: Vec2) -> impl Iterator<Item = (Vec2, Tile)> + '_
+    where
+        Tile: Copy,
+    {
+        self.neighbors4_full(pos)
+            .map(|(_dir, neighbor, tile)| (neighbor, tile))
+    }
+
+    /// Iterate the in-bounds orthogonal and diagonal neighbours of `pos` and
+    /// their tiles
+    pub fn neighbors8(&self, pos: Vec2) -> impl Iterator<Item = (Vec2, Tile)> + '_
+    where
+        Tile: Copy,
+    {
+        Dir8::ALL.into_iter().filter_map(move |dir| {
+            let neighbor = pos + dir;
+            self.get(neighbor).map(|tile| (neighbor, tile))
+        })
+    }
+
+    /// All the positions reachable from `start` by orthogonal moves through
+    /// passable tiles, in the order a BFS visits them.
+    ///
+    /// Returns an empty vec if `start` is itself out of bounds or impassable.
+    pub fn flood_fill(&self, start: Vec2, passable: impl Fn(&Tile) -> bool) -> Vec<Vec2> {
+        let is_passable = |pos: Vec2| self.index_of(pos).is_some_and(|i| passable(&self.data[i]));
+        if !is_passable(start) {
+            return Vec::new();
+        }
+
+        let mut visited = Map2d::new_default(self.size, false);
+        *visited.get_mut(start).unwrap() = true;
+
+        let mut region = Vec::new();
+        let mut queue = std::collections::VecDeque::from([start]);
+        while let Some(pos) = queue.pop_front() {
+            region.push(pos);
+            for dir in Dir::ALL {
+                let next = pos + dir;
+                if is_passable(next) && !visited.get(next).unwrap() {
+                    *visited.get_mut(next).unwrap() = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        region
+    }
+
+    /// Repeatedly apply `step` until it reports that nothing changed.
+    ///
+    /// Returns the number of steps that did change the map, ie not counting
+    /// the final step that confirmed the map is stable.
+    pub fn apply_until_stable(&mut self, mut step: impl FnMut(&mut Map2d<Tile>) -> bool) -> usize {
+        let mut count = 0;
+        while step(self) {
+            count += 1;
+        }
+        count
+    }
 }
 
 impl<Tile> Map2dExt<Tile> for Map2d<Tile> {
@@ -89,6 +468,32 @@ impl<Tile> Map2dExt<Tile> for Map2d<Tile> {
     }
 }
 
+impl<Tile> std::ops::Index<Vec2> for Map2d<Tile> {
+    type Output = Tile;
+
+    fn index(&self, pos: Vec2) -> &Self::Output {
+        match self.index_of(pos) {
+            Some(i) => &self.data[i],
+            None => panic!("Position {pos:?} out of bounds"),
+        }
+    }
+}
+
+impl<Tile> std::ops::IndexMut<Vec2> for Map2d<Tile> {
+    fn index_mut(&mut self, pos: Vec2) -> &mut Self::Output {
+        match self.index_of(pos) {
+            Some(i) => &mut self.data[i],
+            None => panic!("Position {pos:?} out of bounds"),
+        }
+    }
+}
+
+impl<Tile: Into<char> + Copy> std::fmt::Display for Map2d<Tile> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_string_with(|tile| (*tile).into()))
+    }
+}
+
 impl<Tile> AsRef<Map2d<Tile>> for Map2d<Tile> {
     fn as_ref(&self) -> &Map2d<Tile> {
         self
@@ -103,23 +508,35 @@ pub struct RotatedMap2d<'a, Tile> {
     pub up: Dir,
 }
 
+/// The size of a map of size `size` when rotated such that its original 'up'
+/// direction points towards `up`
+fn rotated_size(size: Vec2, up: Dir) -> Vec2 {
+    match up {
+        Dir::Up | Dir::Down => size,
+        Dir::Left | Dir::Right => Vec2::new(size.y, size.x),
+    }
+}
+
+/// Maps a position in the rotated view of a map of size `size` back to the
+/// corresponding position in the original map
+fn rotated_source_pos(size: Vec2, up: Dir, pos: Vec2) -> Vec2 {
+    match up {
+        Dir::Up => pos,
+        Dir::Down => Vec2::new(size.x - pos.x - 1, size.y - pos.y - 1),
+        Dir::Left => Vec2::new(size.x - pos.y - 1, pos.x),
+        Dir::Right => Vec2::new(pos.y, size.y - pos.x - 1),
+    }
+}
+
 impl<'a, Tile> RotatedMap2d<'a, Tile> {
     fn source_pos(&self, pos: Vec2) -> Vec2 {
-        match self.up {
-            Dir::Up => pos,
-            Dir::Down => Vec2::new(self.map.size.x - pos.x - 1, self.map.size.y - pos.y - 1),
-            Dir::Left => Vec2::new(self.map.size.x - pos.y - 1, pos.x),
-            Dir::Right => Vec2::new(pos.y, self.map.size.y - pos.x - 1),
-        }
+        rotated_source_pos(self.map.size, self.up, pos)
     }
 }
 
 impl<'a, Tile> Map2dExt<Tile> for RotatedMap2d<'a, Tile> {
     fn size(&self) -> Vec2 {
-        match self.up {
-            Dir::Up | Dir::Down => self.map.size,
-            Dir::Left | Dir::Right => Vec2::new(self.map.size.y, self.map.size.x),
-        }
+        rotated_size(self.map.size, self.up)
     }
 
     fn get(&self, pos: Vec2) -> Option<Tile>
@@ -133,3 +550,554 @@ impl<'a, Tile> Map2dExt<Tile> for RotatedMap2d<'a, Tile> {
         self.map.get_mut(self.source_pos(pos))
     }
 }
+
+/// A view of the original map under any of the eight symmetries of a
+/// rectangle: rotated as in [`RotatedMap2d`], then optionally mirrored left to
+/// right.
+///
+/// A top to bottom mirror is `up: Dir::Down` with `flipped: true`.
+pub struct TransformedMap2d<'a, Tile> {
+    pub map: &'a mut Map2d<Tile>,
+
+    /// The original 'up' direction of the source map in this view
+    pub up: Dir,
+
+    /// Whether the rotated view is additionally mirrored left to right
+    pub flipped: bool,
+}
+
+impl<'a, Tile> TransformedMap2d<'a, Tile> {
+    fn source_pos(&self, pos: Vec2) -> Vec2 {
+        let pos = if self.flipped {
+            Vec2::new(self.size().x - pos.x - 1, pos.y)
+        } else {
+            pos
+        };
+        rotated_source_pos(self.map.size, self.up, pos)
+    }
+}
+
+impl<'a, Tile> Map2dExt<Tile> for TransformedMap2d<'a, Tile> {
+    fn size(&self) -> Vec2 {
+        rotated_size(self.map.size, self.up)
+    }
+
+    fn get(&self, pos: Vec2) -> Option<Tile>
+    where
+        Tile: Copy,
+    {
+        self.map.get(self.source_pos(pos))
+    }
+
+    fn get_mut(&mut self, pos: Vec2) -> Option<&mut Tile> {
+        self.map.get_mut(self.source_pos(pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::test::random_grid;
+
+    #[test]
+    fn test_try_parse_grid() {
+        let map = Map2d::try_parse_grid("ab\ncd\nef\n", |c| c).unwrap();
+        assert_eq!(map.size, Vec2::new(2, 3));
+        assert_eq!(map.data, vec!['a', 'b', 'c', 'd', 'e', 'f']);
+
+        let jagged = Map2d::try_parse_grid("abc\nde\nfgh", |c| c);
+        assert_eq!(
+            jagged.err(),
+            Some(GridParseError::Jagged {
+                row: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+
+        let empty = Map2d::try_parse_grid("", |c| c);
+        assert_eq!(empty.err(), Some(GridParseError::Empty));
+    }
+
+    #[test]
+    fn test_from_rows() {
+        let map = Map2d::from_rows(vec![vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        assert_eq!(map.size, Vec2::new(3, 2));
+        assert_eq!(map.get(Vec2::new(2, 1)), Some(6));
+        assert_eq!(
+            map.rows().collect::<Vec<_>>(),
+            vec![&[1, 2, 3][..], &[4, 5, 6][..]]
+        );
+
+        let ragged = Map2d::from_rows(vec![vec![1, 2, 3], vec![4, 5]]);
+        assert_eq!(
+            ragged.err(),
+            Some(GridParseError::Jagged {
+                row: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_indexed() {
+        let (map, index) = Map2d::parse_indexed("#.S\n..#", |c| c == '#').unwrap();
+
+        assert_eq!(map.size, Vec2::new(3, 2));
+        assert_eq!(map.data, vec![true, false, false, false, false, true]);
+
+        assert_eq!(index.len(), 3);
+        assert_eq!(index[&'#'], vec![Vec2::new(0, 0), Vec2::new(2, 1)]);
+        assert_eq!(
+            index[&'.'],
+            vec![Vec2::new(1, 0), Vec2::new(0, 1), Vec2::new(1, 1)]
+        );
+        assert_eq!(index[&'S'], vec![Vec2::new(2, 0)]);
+
+        // Rows are measured in chars rather than bytes
+        let (map, index) = Map2d::parse_indexed("é.\n.é", |c| c).unwrap();
+        assert_eq!(map.size, Vec2::new(2, 2));
+        assert_eq!(index[&'é'], vec![Vec2::new(0, 0), Vec2::new(1, 1)]);
+
+        let jagged = Map2d::parse_indexed("#.S\n.#", |c| c);
+        assert_eq!(
+            jagged.err(),
+            Some(GridParseError::Jagged {
+                row: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+
+        let empty = Map2d::parse_indexed("", |c| c);
+        assert_eq!(empty.err(), Some(GridParseError::Empty));
+    }
+
+    #[test]
+    fn test_neighbors4_full() {
+        let map = Map2d::parse_grid("abc\ndef\nghi", |c| c);
+
+        let neighbors = map.neighbors4_full(Vec2::new(1, 1)).collect::<Vec<_>>();
+        assert_eq!(
+            neighbors,
+            vec![
+                (Dir::Up, Vec2::new(1, 0), 'b'),
+                (Dir::Down, Vec2::new(1, 2), 'h'),
+                (Dir::Left, Vec2::new(0, 1), 'd'),
+                (Dir::Right, Vec2::new(2, 1), 'f'),
+            ]
+        );
+
+        let corner = map.neighbors4_full(Vec2::new(0, 0)).collect::<Vec<_>>();
+        assert_eq!(
+            corner,
+            vec![
+                (Dir::Down, Vec2::new(0, 1), 'd'),
+                (Dir::Right, Vec2::new(1, 0), 'b'),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let map = Map2d::parse_grid("abc\ndef\nghi", |c| c);
+
+        let corner = map.neighbors4(Vec2::new(0, 0)).collect::<Vec<_>>();
+        assert_eq!(corner, vec![(Vec2::new(0, 1), 'd'), (Vec2::new(1, 0), 'b')]);
+        assert_eq!(map.neighbors8(Vec2::new(0, 0)).count(), 3);
+
+        let edge = map.neighbors4(Vec2::new(1, 0)).collect::<Vec<_>>();
+        assert_eq!(
+            edge,
+            vec![
+                (Vec2::new(1, 1), 'e'),
+                (Vec2::new(0, 0), 'a'),
+                (Vec2::new(2, 0), 'c'),
+            ]
+        );
+        assert_eq!(map.neighbors8(Vec2::new(1, 0)).count(), 5);
+
+        assert_eq!(map.neighbors4(Vec2::new(1, 1)).count(), 4);
+        let mut interior = map
+            .neighbors8(Vec2::new(1, 1))
+            .map(|(_, c)| c)
+            .collect::<Vec<_>>();
+        interior.sort();
+        assert_eq!(interior, vec!['a', 'b', 'c', 'd', 'f', 'g', 'h', 'i']);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let map = Map2d::parse_grid("..#..\n..#..\n..#..", |c| c);
+
+        let mut left = map.flood_fill(Vec2::new(0, 0), |&c| c == '.');
+        left.sort_by_key(|pos| (pos.y, pos.x));
+        assert_eq!(
+            left,
+            vec![
+                Vec2::new(0, 0),
+                Vec2::new(1, 0),
+                Vec2::new(0, 1),
+                Vec2::new(1, 1),
+                Vec2::new(0, 2),
+                Vec2::new(1, 2),
+            ]
+        );
+
+        let right = map.flood_fill(Vec2::new(4, 2), |&c| c == '.');
+        assert_eq!(right.len(), 6);
+        assert!(right.iter().all(|pos| pos.x > 2));
+
+        assert!(map.flood_fill(Vec2::new(2, 1), |&c| c == '.').is_empty());
+    }
+
+    #[test]
+    fn test_transforms() {
+        let map = Map2d::parse_grid("ab\ncd\nef", |c| c);
+
+        let transposed = map.transpose();
+        assert_eq!(transposed.size, Vec2::new(3, 2));
+        assert_eq!(transposed.to_string(), "ace\nbdf");
+
+        let cw = map.rotate_cw();
+        assert_eq!(cw.size, Vec2::new(3, 2));
+        assert_eq!(cw.to_string(), "eca\nfdb");
+
+        let ccw = map.rotate_ccw();
+        assert_eq!(ccw.size, Vec2::new(3, 2));
+        assert_eq!(ccw.to_string(), "bdf\nace");
+
+        assert_eq!(map.flip_horizontal().to_string(), "ba\ndc\nfe");
+        assert_eq!(map.flip_vertical().to_string(), "ef\ncd\nab");
+
+        assert_eq!(cw.rotate_ccw(), map);
+        assert_eq!(transposed.transpose(), map);
+    }
+
+    #[test]
+    fn test_rotate_cw_four_times_is_identity() {
+        for seed in 0..10 {
+            let size = Vec2::new(1 + seed as i64 % 4, 1 + seed as i64 % 7);
+            let map = random_grid(size, &['#', '.', 'O', 'S'], seed);
+
+            let rotated = map.rotate_cw().rotate_cw().rotate_cw().rotate_cw();
+            assert_eq!(rotated, map);
+        }
+    }
+
+    #[test]
+    fn test_random_grid_is_reproducible() {
+        let a = random_grid(Vec2::new(7, 5), &['#', '.', 'O'], 42);
+        let b = random_grid(Vec2::new(7, 5), &['#', '.', 'O'], 42);
+        assert_eq!(a.size, Vec2::new(7, 5));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_get_col() {
+        let map = Map2d::parse_grid("abc\ndef\nghi", |c| c);
+
+        assert_eq!(map.get_col(0), vec!['a', 'd', 'g']);
+        assert_eq!(map.get_col(2), vec!['c', 'f', 'i']);
+        assert_eq!(map.col_iter(1).collect::<String>(), "beh");
+
+        assert!(map.get_col(-1).is_empty());
+        assert!(map.get_col(3).is_empty());
+        assert_eq!(map.col_iter(3).count(), 0);
+    }
+
+    #[test]
+    fn test_clone_eq() {
+        let map = Map2d::parse_grid("ab\ncd", |c| c);
+        let mut cloned = map.clone();
+        assert_eq!(cloned, map);
+
+        *cloned.get_mut(Vec2::new(1, 1)).unwrap() = 'x';
+        assert_ne!(cloned, map);
+
+        // Same data but a different shape is a different map
+        let reshaped = Map2d::parse_grid("abcd", |c| c);
+        assert_ne!(reshaped, map);
+    }
+
+    #[test]
+    fn test_set() {
+        let mut map = Map2d::parse_grid("ab\ncd", |c| c);
+
+        assert!(map.set(Vec2::new(1, 0), 'x'));
+        assert_eq!(map.to_string(), "ax\ncd");
+
+        assert!(!map.set(Vec2::new(2, 0), 'y'));
+        assert!(!map.set(Vec2::new(0, -1), 'y'));
+        assert_eq!(map.to_string(), "ax\ncd");
+    }
+
+    #[test]
+    fn test_index() {
+        let mut map = Map2d::parse_grid("ab\ncd", |c| c);
+        assert_eq!(map[Vec2::new(0, 1)], 'c');
+
+        map[Vec2::new(0, 1)] = 'z';
+        assert_eq!(map.to_string(), "ab\nzd");
+    }
+
+    #[test]
+    fn test_get_wrapping() {
+        let map = Map2d::parse_grid("abc\ndef", |c| c);
+        assert_eq!(map.get_wrapping(Vec2::new(3, 0)), 'a');
+        assert_eq!(map.get_wrapping(Vec2::new(3, 1)), 'd');
+        assert_eq!(map.get_wrapping(Vec2::new(-1, 0)), 'c');
+        assert_eq!(map.get_wrapping(Vec2::new(1, -1)), 'e');
+        assert_eq!(map.get_wrapping(Vec2::new(7, 4)), 'b');
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_out_of_bounds() {
+        let map = Map2d::parse_grid("ab\ncd", |c| c);
+        let _ = map[Vec2::new(2, 0)];
+    }
+
+    #[test]
+    fn test_crop() {
+        let map = Map2d::parse_grid("abcd\nefgh\nijkl", |c| c);
+
+        let interior = map.crop(Vec2::new(1, 1), Vec2::new(2, 2)).unwrap();
+        assert_eq!(interior.size, Vec2::new(2, 2));
+        assert_eq!(interior.to_string(), "fg\njk");
+
+        assert_eq!(map.crop(Vec2::zero(), map.size), Some(map.clone()));
+
+        assert_eq!(map.crop(Vec2::new(2, 1), Vec2::new(3, 1)), None);
+        assert_eq!(map.crop(Vec2::new(-1, 0), Vec2::new(2, 2)), None);
+    }
+
+    #[test]
+    fn test_with_border() {
+        let map = Map2d::parse_grid("ab\ncd\nef", |c| c);
+
+        let bordered = map.with_border(2, '.');
+        assert_eq!(bordered.size, Vec2::new(6, 7));
+        for (pos, tile) in map.iter() {
+            assert_eq!(bordered[pos + Vec2::new(2, 2)], *tile);
+        }
+        assert_eq!(bordered.find(|&c| c != '.'), Some(Vec2::new(2, 2)));
+        assert_eq!(bordered.data.iter().filter(|&&c| c == '.').count(), 36);
+
+        assert_eq!(map.with_border(0, '.'), map);
+    }
+
+    #[test]
+    fn test_rotated_to() {
+        let map = Map2d::parse_grid("abc\ndef", |c| c);
+
+        for up in Dir::ALL {
+            let rotated = map.rotated_to(up);
+
+            let mut source = map.clone();
+            let view = RotatedMap2d {
+                map: &mut source,
+                up,
+            };
+
+            assert_eq!(rotated.size, view.size());
+            for (pos, tile) in rotated.iter() {
+                assert_eq!(view.get(pos), Some(*tile));
+            }
+        }
+
+        assert_eq!(map.rotated_to(Dir::Left).to_string(), "cf\nbe\nad");
+        assert_eq!(map.rotated_to(Dir::Up), map);
+    }
+
+    #[test]
+    fn test_count_and_find_all() {
+        let map = Map2d::parse_grid("O.#\n.O#\nO..", |c| c);
+
+        assert_eq!(map.count(|&c| c == 'O'), 3);
+        assert_eq!(map.count(|&c| c == 'x'), 0);
+        assert_eq!(
+            map.find_all(|&c| c == 'O'),
+            vec![Vec2::new(0, 0), Vec2::new(1, 1), Vec2::new(0, 2)]
+        );
+        assert_eq!(map.find_all(|&c| c == '#').len(), 2);
+    }
+
+    #[test]
+    fn test_map() {
+        let map = Map2d::parse_grid("#.#\n..#", |c| c);
+        let walls = map.map(|&c| c == '#');
+
+        assert_eq!(walls.size, map.size);
+        assert!(walls[Vec2::new(0, 0)]);
+        assert!(!walls[Vec2::new(1, 0)]);
+        assert!(!walls[Vec2::new(0, 1)]);
+        assert!(walls[Vec2::new(2, 1)]);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let raw = "#.S\n..#\nO.O";
+        let map = Map2d::parse_grid(raw, |c| c);
+        assert_eq!(map.to_string(), raw);
+
+        let bools = Map2d::parse_grid(raw, |c| c == '#');
+        let rendered = bools.to_string_with(|&b| if b { '#' } else { '.' });
+        assert_eq!(rendered, "#..\n..#\n...");
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut map = Map2d::parse_grid("abc\ndef", |c| c);
+
+        let expected = vec![
+            (Vec2::new(0, 0), 'a'),
+            (Vec2::new(1, 0), 'b'),
+            (Vec2::new(2, 0), 'c'),
+            (Vec2::new(0, 1), 'd'),
+            (Vec2::new(1, 1), 'e'),
+            (Vec2::new(2, 1), 'f'),
+        ];
+        let seen = map.iter().map(|(pos, c)| (pos, *c)).collect::<Vec<_>>();
+        assert_eq!(seen, expected);
+
+        let seen = map.iter_mut().map(|(pos, c)| (pos, *c)).collect::<Vec<_>>();
+        assert_eq!(seen, expected);
+
+        for (pos, c) in map.iter_mut() {
+            if pos.y == 1 {
+                *c = c.to_ascii_uppercase();
+            }
+        }
+        assert_eq!(map.data, vec!['a', 'b', 'c', 'D', 'E', 'F']);
+    }
+
+    #[test]
+    fn test_apply_until_stable() {
+        let mut map = Map2d::parse_grid("036\n142", |c| c.to_digit(10).unwrap());
+
+        // Each step decrements every non-zero tile, so the map stabilises once
+        // the largest tile reaches zero
+        let steps = map.apply_until_stable(|map| {
+            let mut changed = false;
+            for tile in map.data.iter_mut().filter(|tile| **tile > 0) {
+                *tile -= 1;
+                changed = true;
+            }
+            changed
+        });
+
+        assert_eq!(steps, 6);
+        assert!(map.data.iter().all(|tile| *tile == 0));
+    }
+
+    #[test]
+    fn test_transformed_view() {
+        let map = Map2d::parse_grid("abc\ndef", |c| c);
+
+        // Where the 'a' in the top left corner ends up in each view
+        let cases = [
+            (Dir::Up, false, Vec2::new(0, 0)),
+            (Dir::Up, true, Vec2::new(2, 0)),
+            (Dir::Down, false, Vec2::new(2, 1)),
+            (Dir::Down, true, Vec2::new(0, 1)),
+            (Dir::Left, false, Vec2::new(0, 2)),
+            (Dir::Left, true, Vec2::new(1, 2)),
+            (Dir::Right, false, Vec2::new(1, 0)),
+            (Dir::Right, true, Vec2::new(0, 0)),
+        ];
+
+        for (up, flipped, a_pos) in cases {
+            let expected = if flipped {
+                map.rotated_to(up).flip_horizontal()
+            } else {
+                map.rotated_to(up)
+            };
+
+            let mut source = map.clone();
+            let mut view = TransformedMap2d {
+                map: &mut source,
+                up,
+                flipped,
+            };
+
+            assert_eq!(view.size(), expected.size);
+            assert_eq!(view.get(a_pos), Some('a'));
+            for (pos, tile) in expected.iter() {
+                assert_eq!(view.get(pos), Some(*tile));
+            }
+
+            // Writes go through the same transform as reads
+            *view.get_mut(a_pos).unwrap() = 'x';
+            assert_eq!(source[Vec2::new(0, 0)], 'x');
+        }
+
+        let mut source = map.clone();
+        let view = TransformedMap2d {
+            map: &mut source,
+            up: Dir::Down,
+            flipped: true,
+        };
+        assert_eq!(view.get(Vec2::new(0, 0)), Some('d'));
+    }
+
+    /// A minimal grid stored as nested rows, to check the trait contract is
+    /// enough to write algorithms against
+    struct NestedRows(Vec<Vec<char>>);
+
+    impl Map2dExt<char> for NestedRows {
+        fn size(&self) -> Vec2 {
+            Vec2::new(self.0[0].len() as i64, self.0.len() as i64)
+        }
+
+        fn get(&self, pos: Vec2) -> Option<char> {
+            let row = self.0.get(usize::try_from(pos.y).ok()?)?;
+            row.get(usize::try_from(pos.x).ok()?).copied()
+        }
+
+        fn get_mut(&mut self, pos: Vec2) -> Option<&mut char> {
+            let row = self.0.get_mut(usize::try_from(pos.y).ok()?)?;
+            row.get_mut(usize::try_from(pos.x).ok()?)
+        }
+    }
+
+    /// Roll every 'O' as far up its column as it can go, stopping at '#'
+    fn slide_up(map: &mut impl Map2dExt<char>) {
+        for x in 0..map.size().x {
+            let mut free_y = 0;
+            for y in 0..map.size().y {
+                match map.get(Vec2::new(x, y)) {
+                    Some('#') => free_y = y + 1,
+                    Some('O') => {
+                        *map.get_mut(Vec2::new(x, y)).unwrap() = '.';
+                        *map.get_mut(Vec2::new(x, free_y)).unwrap() = 'O';
+                        free_y += 1;
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_map2d_ext_custom_impl() {
+        let input = "..O\nO#.\n.O.\nO.O";
+        let mut nested = NestedRows(input.lines().map(|l| l.chars().collect()).collect());
+        let mut map = Map2d::parse_grid(input, |c| c);
+
+        slide_up(&mut nested);
+        slide_up(&mut map);
+
+        assert_eq!(nested.get(Vec2::new(-1, 0)), None);
+        assert_eq!(nested.get(Vec2::new(0, 4)), None);
+        let nested_string = nested
+            .0
+            .iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(nested_string, "O.O\nO#O\n.O.\n...");
+        assert_eq!(map.to_string(), nested_string);
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A cache of the results of some function, for memoizing recursive solves
+/// without threading a `HashMap` through by hand
+#[derive(Clone, Debug)]
+pub struct Memoizer<K, V> {
+    cache: HashMap<K, V>,
+}
+
+impl<K, V> Memoizer<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            cache: HashMap::new(),
+        }
+    }
+
+    /// The cached result for `key`, computing it with `f` if there isn't one
+    /// yet.
+    ///
+    /// `f` is handed the memoizer itself so that it can recurse through it.
+    pub fn get_or_compute(&mut self, key: K, f: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(value) = self.cache.get(&key) {
+            return value.clone();
+        }
+
+        let value = f(self);
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    /// The number of distinct keys with a cached result
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+}
+
+impl<K, V> Default for Memoizer<K, V>
+where
+    K: Hash + Eq,
+    V: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    fn fib(n: u64, memo: &mut Memoizer<u64, u64>, calls: &Cell<usize>) -> u64 {
+        memo.get_or_compute(n, |memo| {
+            calls.set(calls.get() + 1);
+            if n < 2 {
+                n
+            } else {
+                fib(n - 1, memo, calls) + fib(n - 2, memo, calls)
+            }
+        })
+    }
+
+    #[test]
+    fn test_fibonacci() {
+        let mut memo = Memoizer::new();
+        let calls = Cell::new(0);
+
+        assert_eq!(fib(80, &mut memo, &calls), 23_416_728_348_467_685);
+
+        // Each value is only computed once, despite the naive recursion
+        assert_eq!(calls.get(), 81);
+        assert_eq!(memo.len(), 81);
+
+        // Anything already seen comes straight from the cache
+        assert_eq!(fib(50, &mut memo, &calls), 12_586_269_025);
+        assert_eq!(calls.get(), 81);
+    }
+}
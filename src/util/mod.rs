@@ -1,12 +1,20 @@
 pub mod combinatorial;
 pub mod dir;
 pub mod map2d;
+pub mod memo;
 pub mod numbers;
+pub mod parse;
+pub mod ratio;
+#[cfg(test)]
+pub mod test;
 pub mod vec2;
 pub mod graph;
 
 pub use combinatorial::*;
-pub use dir::Dir;
-pub use map2d::{Map2d, Map2dExt, RotatedMap2d};
+pub use dir::{Dir, Dir8, Turn};
+pub use map2d::{GridParseError, Map2d, Map2dExt, RotatedMap2d, TransformedMap2d};
+pub use memo::Memoizer;
 pub use numbers::*;
-pub use vec2::Vec2;
+pub use parse::{parse_grid_of_ints, parse_ints, try_parse_grid_of_ints, IntParseError};
+pub use ratio::Ratio;
+pub use vec2::{line, polygon_area, polygon_area_trapezoid, Vec2};
@@ -1,3 +1,4 @@
+/// The greatest common divisor of `a` and `b`, which is always non-negative
 pub fn gcm(a: i64, b: i64) -> i64 {
     let mut a = a;
     let mut b = b;
@@ -6,22 +7,138 @@ pub fn gcm(a: i64, b: i64) -> i64 {
         b = a % b;
         a = t;
     }
-    a
+    a.abs()
 }
 
+/// The least common multiple of `a` and `b`, which is always non-negative,
+/// or None if it doesn't fit in an i64
+pub fn checked_lcm(a: i64, b: i64) -> Option<i64> {
+    match gcm(a, b) {
+        0 => Some(0),
+        g => (a / g).checked_mul(b)?.checked_abs(),
+    }
+}
+
+/// As [`checked_lcm`], panicking on overflow
 pub fn lcm(a: i64, b: i64) -> i64 {
-    a * b / gcm(a, b)
+    checked_lcm(a, b).expect("lcm overflowed")
 }
 
-pub fn lcm_iter<I>(mut iter: I) -> i64
+/// The least common multiple of every value in `iter`, or None if it doesn't
+/// fit in an i64.
+///
+/// The lcm of no values at all is 1.
+pub fn checked_lcm_iter<I>(iter: I) -> Option<i64>
 where
     I: Iterator<Item = i64>,
 {
-    let mut result = iter.next().unwrap();
+    let mut result = 1;
     for i in iter {
-        result = lcm(result, i);
+        result = checked_lcm(result, i)?;
     }
-    result
+    Some(result)
+}
+
+/// As [`checked_lcm_iter`], panicking on overflow
+pub fn lcm_iter<I>(iter: I) -> i64
+where
+    I: Iterator<Item = i64>,
+{
+    checked_lcm_iter(iter).expect("lcm overflowed")
+}
+
+/// Whether `n` is prime.
+///
+/// Uses trial division below 2^32, and a Miller-Rabin test above that, which
+/// is deterministic for every i64 with these bases.
+pub fn is_prime(n: i64) -> bool {
+    const BASES: [i64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+
+    if n < 1 << 32 {
+        // Dividing rather than squaring d, so the bound can't overflow
+        let mut d = 2;
+        while d <= n / d {
+            if n % d == 0 {
+                return false;
+            }
+            d += 1;
+        }
+        return true;
+    }
+
+    if BASES.iter().any(|&p| n % p == 0) {
+        return false;
+    }
+
+    // n - 1 = odd * 2^twos
+    let twos = (n - 1).trailing_zeros();
+    let odd = (n - 1) >> twos;
+
+    BASES.iter().all(|&base| {
+        let mut x = mod_pow(base, odd as u64, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+
+        for _ in 1..twos {
+            x = (x as i128 * x as i128 % n as i128) as i64;
+            if x == n - 1 {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// The prime factorization of `n`, as `(prime, exponent)` pairs in ascending
+/// order of prime.
+///
+/// Anything less than 2 has no prime factors.
+pub fn prime_factors(n: i64) -> Vec<(i64, u32)> {
+    let mut factors = Vec::new();
+    let mut n = n;
+    let mut d = 2;
+    let mut n_changed = true;
+    while d <= n / d {
+        // Trial division would take far too long to get through a large prime
+        // factor, so stop as soon as what's left is prime
+        if n_changed && is_prime(n) {
+            break;
+        }
+        n_changed = false;
+
+        let mut exponent = 0;
+        while n % d == 0 {
+            n /= d;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            factors.push((d, exponent));
+            n_changed = true;
+        }
+        d += 1;
+    }
+
+    // Whatever is left has no factors up to its square root
+    if n > 1 {
+        factors.push((n, 1));
+    }
+    factors
+}
+
+/// The `n`th triangular number, ie the sum of the integers `1..=n`
+pub fn triangular(n: i64) -> i64 {
+    n * (n + 1) / 2
+}
+
+/// The sum of `count` evenly spaced integers running from `first` to `last`
+/// inclusive, in either direction
+pub fn arithmetic_sum(first: i64, last: i64, count: i64) -> i64 {
+    (first + last) * count / 2
 }
 
 /// Return the number of ways to choose k items from n items without repetition
@@ -35,8 +152,222 @@ pub fn binomial_coefficient(n: i64, k: i64) -> i64 {
     result
 }
 
+/// As [`binomial_coefficient`], but accumulating in a u128 so that it stays
+/// exact for much larger `n`.
+///
+/// Returns 0 when `k > n`.
+pub fn binomial_coefficient_u128(n: u64, k: u64) -> u128 {
+    if k > n {
+        return 0;
+    }
+
+    // C(n, k) = C(n, n - k), so take the shorter of the two products
+    let k = k.min(n - k);
+
+    // After each step the result is exactly C(n, i + 1), so multiplying
+    // before dividing means the division never truncates
+    let mut result = 1u128;
+    for i in 0..k as u128 {
+        result *= n as u128 - i;
+        result /= i + 1;
+    }
+    result
+}
+
+/// Computes `base ^ exp mod modulus`, with the result normalized into
+/// `[0, modulus)` even for negative bases
+pub fn mod_pow(base: i64, exp: u64, modulus: i64) -> i64 {
+    let modulus = modulus as i128;
+    let mut base = (base as i128).rem_euclid(modulus);
+    let mut exp = exp;
+    let mut result = 1 % modulus;
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+
+    result as i64
+}
+
+/// Returns `(g, x, y)` such that `a*x + b*y = g = gcd(a, b)`
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// The multiplicative inverse of `a` modulo `modulus`, normalized into
+/// `[0, modulus)`, or None if `a` and `modulus` aren't coprime
+pub fn mod_inverse(a: i64, modulus: i64) -> Option<i64> {
+    let (g, x, _) = extended_gcd(a.rem_euclid(modulus), modulus);
+    if g == 1 {
+        Some(x.rem_euclid(modulus))
+    } else {
+        None
+    }
+}
+
+/// The smallest non-negative `x` satisfying `x = r (mod m)` for every
+/// `(r, m)` pair, using the Chinese Remainder Theorem.
+///
+/// The moduli needn't be coprime. Returns None if the congruences are
+/// inconsistent with one another.
+pub fn crt(residues: &[(i64, i64)]) -> Option<i64> {
+    let mut result = 0;
+    let mut modulus = 1;
+
+    for &(r, m) in residues {
+        // Looking for the k such that `result + modulus * k = r (mod m)`
+        let (g, inverse, _) = extended_gcd(modulus, m);
+        let diff = r - result;
+        if diff % g != 0 {
+            return None;
+        }
+
+        let step = m / g;
+        let k = ((diff / g) as i128 * inverse as i128).rem_euclid(step as i128) as i64;
+        let combined_modulus = modulus * step;
+        result = (result + modulus * k).rem_euclid(combined_modulus);
+        modulus = combined_modulus;
+    }
+
+    Some(result)
+}
+
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn test_gcm() {
+        assert_eq!(super::gcm(12, 18), 6);
+        assert_eq!(super::gcm(-12, 18), 6);
+        assert_eq!(super::gcm(12, -18), 6);
+        assert_eq!(super::gcm(-12, -18), 6);
+        assert_eq!(super::gcm(0, -5), 5);
+        assert_eq!(super::gcm(-5, 0), 5);
+        assert_eq!(super::gcm(0, 0), 0);
+    }
+
+    #[test]
+    fn test_lcm() {
+        assert_eq!(super::lcm(4, 6), 12);
+        assert_eq!(super::lcm(-4, 6), 12);
+        assert_eq!(super::lcm(4, -6), 12);
+        assert_eq!(super::lcm(-4, -6), 12);
+        assert_eq!(super::lcm(0, 5), 0);
+        assert_eq!(super::lcm(-5, 0), 0);
+        assert_eq!(super::lcm(0, 0), 0);
+    }
+
+    #[test]
+    fn test_checked_lcm() {
+        // The naive product of these overflows, but they share a large factor
+        let c: i64 = 100_000_000_000_000_003;
+        let (a, b) = (3 * c, 5 * c);
+        assert!(a.checked_mul(b).is_none());
+        assert_eq!(super::checked_lcm(a, b), Some(15 * c));
+        assert_eq!(super::checked_lcm(-a, b), Some(15 * c));
+        assert_eq!(super::lcm(a, b), 15 * c);
+
+        // Whereas for coprime values the lcm really is the product
+        assert_eq!(
+            super::checked_lcm(1_000_000_007, 1_000_000_009),
+            Some(1_000_000_016_000_000_063)
+        );
+        assert_eq!(super::checked_lcm(4_000_000_007, 4_000_000_009), None);
+
+        assert_eq!(super::checked_lcm_iter([a, b, 2].into_iter()), Some(30 * c));
+        assert_eq!(super::checked_lcm_iter([a, b, 7].into_iter()), None);
+    }
+
+    #[test]
+    fn test_lcm_iter() {
+        assert_eq!(super::lcm_iter(std::iter::empty()), 1);
+        assert_eq!(super::lcm_iter([6].into_iter()), 6);
+        assert_eq!(super::lcm_iter([-6].into_iter()), 6);
+        assert_eq!(super::lcm_iter([4, 6, 10].into_iter()), 60);
+        assert_eq!(super::lcm_iter([4, 0, 10].into_iter()), 0);
+    }
+
+    #[test]
+    fn test_is_prime() {
+        let primes = (0..30).filter(|&n| super::is_prime(n)).collect::<Vec<_>>();
+        assert_eq!(primes, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert!(!super::is_prime(-7));
+        assert!(!super::is_prime(49));
+        assert!(super::is_prime(1_000_000_007));
+
+        // Either side of the switch to Miller-Rabin
+        assert!(super::is_prime(3_037_000_493));
+        assert!(super::is_prime(4_294_967_311));
+        assert!(!super::is_prime(4_294_967_297));
+
+        // The largest prime that fits in an i64, and a composite above it
+        assert!(super::is_prime(i64::MAX - 24));
+        assert!(!super::is_prime(i64::MAX));
+        assert!(!super::is_prime(3_037_000_493 * 3_037_000_493));
+    }
+
+    #[test]
+    fn test_prime_factors() {
+        assert_eq!(super::prime_factors(1), vec![]);
+        assert_eq!(super::prime_factors(0), vec![]);
+        assert_eq!(super::prime_factors(13), vec![(13, 1)]);
+        assert_eq!(super::prime_factors(49), vec![(7, 2)]);
+        assert_eq!(super::prime_factors(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(
+            super::prime_factors(2 * 1_000_000_007),
+            vec![(2, 1), (1_000_000_007, 1)]
+        );
+
+        assert_eq!(
+            super::prime_factors(i64::MAX - 24),
+            vec![(i64::MAX - 24, 1)]
+        );
+        assert_eq!(
+            super::prime_factors(2 * 4_611_686_018_427_387_847),
+            vec![(2, 1), (4_611_686_018_427_387_847, 1)]
+        );
+        assert_eq!(
+            super::prime_factors(i64::MAX),
+            vec![(7, 2), (73, 1), (127, 1), (337, 1), (92737, 1), (649657, 1)]
+        );
+    }
+
+    #[test]
+    fn test_triangular() {
+        assert_eq!(super::triangular(0), 0);
+        assert_eq!(super::triangular(1), 1);
+        assert_eq!(super::triangular(4), 10);
+        assert_eq!(super::triangular(100), 5050);
+    }
+
+    #[test]
+    fn test_arithmetic_sum() {
+        assert_eq!(super::arithmetic_sum(1, 4, 4), 10);
+        assert_eq!(super::arithmetic_sum(3, 15, 5), 45);
+        assert_eq!(super::arithmetic_sum(10, 1, 10), 55);
+        assert_eq!(super::arithmetic_sum(9, -3, 5), 15);
+        assert_eq!(super::arithmetic_sum(7, 7, 1), 7);
+        assert_eq!(super::arithmetic_sum(0, 0, 0), 0);
+    }
+
     #[test]
     fn test_binomial_coefficient() {
         assert_eq!(super::binomial_coefficient(5, 3), 10);
@@ -44,4 +375,52 @@ mod tests {
         assert_eq!(super::binomial_coefficient(5, 1), 5);
         assert_eq!(super::binomial_coefficient(5, 0), 1);
     }
+
+    #[test]
+    fn test_binomial_coefficient_u128() {
+        assert_eq!(super::binomial_coefficient_u128(5, 2), 10);
+        assert_eq!(super::binomial_coefficient_u128(5, 0), 1);
+        assert_eq!(super::binomial_coefficient_u128(5, 5), 1);
+        assert_eq!(super::binomial_coefficient_u128(5, 6), 0);
+        assert_eq!(super::binomial_coefficient_u128(34, 17), 2_333_606_220);
+        assert_eq!(
+            super::binomial_coefficient_u128(67, 33),
+            14_226_520_737_620_288_370
+        );
+        assert_eq!(
+            super::binomial_coefficient_u128(100, 50),
+            100_891_344_545_564_193_334_812_497_256
+        );
+    }
+
+    #[test]
+    fn test_mod_pow() {
+        assert_eq!(super::mod_pow(2, 10, 1000), 24);
+        assert_eq!(super::mod_pow(3, 0, 7), 1);
+        assert_eq!(super::mod_pow(-2, 3, 5), 2);
+        assert_eq!(super::mod_pow(5, 3, 1), 0);
+        assert_eq!(
+            super::mod_pow(123_456_789, 1_000_000, 1_000_000_007),
+            471_040_903
+        );
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        assert_eq!(super::mod_inverse(3, 11), Some(4));
+        assert_eq!(super::mod_inverse(-3, 11), Some(7));
+        assert_eq!(super::mod_inverse(10, 17), Some(12));
+        assert_eq!(super::mod_inverse(4, 8), None);
+        assert_eq!(super::mod_inverse(0, 5), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(super::crt(&[(2, 3), (3, 5), (2, 7)]), Some(23));
+        assert_eq!(super::crt(&[(0, 4), (0, 6)]), Some(0));
+        assert_eq!(super::crt(&[(1, 4), (3, 6)]), Some(9));
+        assert_eq!(super::crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(super::crt(&[(-1, 5)]), Some(4));
+        assert_eq!(super::crt(&[]), Some(0));
+    }
 }
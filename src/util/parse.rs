@@ -0,0 +1,83 @@
+/// A token in a block of integers that couldn't be parsed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntParseError {
+    /// The zero based index of the line containing the bad token
+    pub line: usize,
+    pub token: String,
+}
+
+impl std::fmt::Display for IntParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid integer {:?} on line {}", self.token, self.line)
+    }
+}
+
+impl std::error::Error for IntParseError {}
+
+/// Parse each line of whitespace separated integers into a row
+pub fn try_parse_grid_of_ints(input: &str) -> Result<Vec<Vec<i64>>, IntParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(line, row)| {
+            row.split_whitespace()
+                .map(|token| {
+                    token.parse().map_err(|_| IntParseError {
+                        line,
+                        token: token.to_string(),
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// As [`try_parse_grid_of_ints`], panicking on invalid input
+pub fn parse_grid_of_ints(input: &str) -> Vec<Vec<i64>> {
+    try_parse_grid_of_ints(input).expect("Invalid integer grid")
+}
+
+/// Extracts every integer from `s`, treating any character other than a digit
+/// or sign as a separator
+pub fn parse_ints(s: &str) -> Vec<i64> {
+    s.split(|c: char| !(c.is_ascii_digit() || c == '-' || c == '+'))
+        .filter(|token| !token.is_empty())
+        .map(|token| token.parse().expect("Invalid integer"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_grid_of_ints() {
+        let rows = parse_grid_of_ints("0 3 6 9\n-1 -12  4\n\n42");
+        assert_eq!(
+            rows,
+            vec![vec![0, 3, 6, 9], vec![-1, -12, 4], vec![], vec![42]]
+        );
+    }
+
+    #[test]
+    fn test_try_parse_grid_of_ints_error() {
+        let err = try_parse_grid_of_ints("1 2\n3 x4 5").unwrap_err();
+        assert_eq!(
+            err,
+            IntParseError {
+                line: 1,
+                token: "x4".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_ints() {
+        assert_eq!(
+            parse_ints("19, 13, 30 @ -2,  1, -2"),
+            vec![19, 13, 30, -2, 1, -2]
+        );
+        assert_eq!(parse_ints("x=+4,y=-7"), vec![4, -7]);
+        assert!(parse_ints("").is_empty());
+    }
+}
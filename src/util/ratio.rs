@@ -0,0 +1,139 @@
+use std::cmp::Ordering;
+
+/// An exact rational number, always kept in lowest terms with a positive
+/// denominator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    num: i128,
+    den: i128,
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let mut a = a.abs();
+    let mut b = b.abs();
+    while b != 0 {
+        let t = b;
+        b = a % b;
+        a = t;
+    }
+    a
+}
+
+impl Ratio {
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "Ratio with a zero denominator");
+
+        let sign = den.signum();
+        let divisor = gcd(num, den);
+        Self {
+            num: sign * num / divisor,
+            den: sign * den / divisor,
+        }
+    }
+
+    pub fn from_i64(value: i64) -> Self {
+        Self {
+            num: value as i128,
+            den: 1,
+        }
+    }
+
+    pub fn num(&self) -> i128 {
+        self.num
+    }
+
+    pub fn den(&self) -> i128 {
+        self.den
+    }
+
+    /// The largest integer less than or equal to this ratio
+    pub fn floor(&self) -> i64 {
+        self.num.div_euclid(self.den) as i64
+    }
+
+    /// The smallest integer greater than or equal to this ratio
+    pub fn ceil(&self) -> i64 {
+        -((-self.num).div_euclid(self.den)) as i64
+    }
+}
+
+impl PartialOrd for Ratio {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ratio {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Both denominators are positive, so cross multiplying preserves the order
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+impl std::ops::Add<Self> for Ratio {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Ratio::new(self.num * rhs.den + rhs.num * self.den, self.den * rhs.den)
+    }
+}
+
+impl std::ops::Sub<Self> for Ratio {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Ratio::new(self.num * rhs.den - rhs.num * self.den, self.den * rhs.den)
+    }
+}
+
+impl std::ops::Mul<Self> for Ratio {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Ratio::new(self.num * rhs.num, self.den * rhs.den)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduction() {
+        assert_eq!(Ratio::new(6, 4), Ratio::new(3, 2));
+        assert_eq!(Ratio::new(3, -6), Ratio::new(-1, 2));
+        assert_eq!(Ratio::new(-3, -6), Ratio::new(1, 2));
+        assert_eq!(Ratio::new(0, 5), Ratio::from_i64(0));
+
+        let r = Ratio::new(10, -4);
+        assert_eq!((r.num(), r.den()), (-5, 2));
+    }
+
+    #[test]
+    fn test_comparison() {
+        assert!(Ratio::new(1, 3) < Ratio::new(1, 2));
+        assert!(Ratio::new(-1, 2) < Ratio::new(-1, 3));
+        assert!(Ratio::new(7, 2) > Ratio::from_i64(3));
+        assert_eq!(Ratio::new(2, 4).cmp(&Ratio::new(1, 2)), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Ratio::new(1, 2);
+        let b = Ratio::new(1, 3);
+        assert_eq!(a + b, Ratio::new(5, 6));
+        assert_eq!(a - b, Ratio::new(1, 6));
+        assert_eq!(a * b, Ratio::new(1, 6));
+        assert_eq!(b - a, Ratio::new(-1, 6));
+    }
+
+    #[test]
+    fn test_floor_ceil() {
+        assert_eq!(Ratio::new(7, 2).floor(), 3);
+        assert_eq!(Ratio::new(7, 2).ceil(), 4);
+        assert_eq!(Ratio::new(-7, 2).floor(), -4);
+        assert_eq!(Ratio::new(-7, 2).ceil(), -3);
+        assert_eq!(Ratio::from_i64(5).floor(), 5);
+        assert_eq!(Ratio::from_i64(5).ceil(), 5);
+    }
+}
@@ -0,0 +1,16 @@
+//! Fixtures for testing the grid utilities
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use super::{Map2d, Vec2};
+
+/// A reproducible grid of the given size, with each tile drawn at random from
+/// `alphabet`
+pub fn random_grid(size: Vec2, alphabet: &[char], seed: u64) -> Map2d<char> {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let data = (0..size.x * size.y)
+        .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+        .collect();
+
+    Map2d { size, data }
+}
@@ -1,28 +1,68 @@
+use std::ops::{Add, Mul, Sub};
 
+/// A 2d vector, generic over its scalar type.
+///
+/// The scalar type defaults to `i64`, so a bare `Vec2` is the integer grid
+/// vector used throughout the solutions.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub struct Vec2 {
-    pub x: i64,
-    pub y: i64,
+pub struct Vec2<T = i64> {
+    pub x: T,
+    pub y: T,
 }
 
-impl Vec2 {
-    pub fn new(x: i64, y: i64) -> Self {
+impl<T> Vec2<T> {
+    pub fn new(x: T, y: T) -> Self {
         Self { x, y }
     }
+}
 
+impl<T: Default> Vec2<T> {
     pub fn zero() -> Self {
-        Self { x: 0, y: 0 }
+        Self {
+            x: T::default(),
+            y: T::default(),
+        }
+    }
+}
+
+impl<T> Vec2<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + PartialOrd + Default + Copy,
+{
+    pub fn l1_norm(self) -> T {
+        let abs = |v: T| {
+            if v < T::default() {
+                T::default() - v
+            } else {
+                v
+            }
+        };
+        abs(self.x) + abs(self.y)
+    }
+}
+
+impl<T> Vec2<T>
+where
+    T: Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Copy,
+{
+    pub fn dot(self, other: Self) -> T {
+        self.x * other.x + self.y * other.y
     }
 
-    pub fn l1_norm(self) -> i64 {
-        self.x.abs() + self.y.abs()
+    /// The z component of the 3d cross product of the two vectors.
+    ///
+    /// Positive when `other` is anticlockwise of `self` in a y-up coordinate
+    /// system (so clockwise on screen with y pointing down), and zero when the
+    /// vectors are parallel.
+    pub fn cross(self, other: Self) -> T {
+        self.x * other.y - self.y * other.x
     }
 }
 
-impl std::ops::Mul<i64> for Vec2 {
+impl<T: Mul<Output = T> + Copy> Mul<T> for Vec2<T> {
     type Output = Self;
 
-    fn mul(self, rhs: i64) -> Self::Output {
+    fn mul(self, rhs: T) -> Self::Output {
         Self {
             x: self.x * rhs,
             y: self.y * rhs,
@@ -30,7 +70,7 @@ impl std::ops::Mul<i64> for Vec2 {
     }
 }
 
-impl std::ops::Add<Self> for Vec2 {
+impl<T: Add<Output = T> + Copy> Add<Self> for Vec2<T> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
@@ -41,21 +81,21 @@ impl std::ops::Add<Self> for Vec2 {
     }
 }
 
-impl std::ops::Add<Self> for &Vec2 {
-    type Output = Vec2;
+impl<T: Add<Output = T> + Copy> Add<Self> for &Vec2<T> {
+    type Output = Vec2<T>;
 
     fn add(self, rhs: Self) -> Self::Output {
         *self + *rhs
     }
 }
 
-impl std::ops::AddAssign<Self> for Vec2 {
+impl<T: Add<Output = T> + Copy> std::ops::AddAssign<Self> for Vec2<T> {
     fn add_assign(&mut self, rhs: Self) {
         *self = *self + rhs;
     }
 }
 
-impl std::ops::Sub<Self> for Vec2 {
+impl<T: Sub<Output = T> + Copy> Sub<Self> for Vec2<T> {
     type Output = Self;
 
     fn sub(self, rhs: Self) -> Self::Output {
@@ -66,16 +106,181 @@ impl std::ops::Sub<Self> for Vec2 {
     }
 }
 
-impl std::ops::Sub<Self> for &Vec2 {
-    type Output = Vec2;
+impl<T: Sub<Output = T> + Copy> Sub<Self> for &Vec2<T> {
+    type Output = Vec2<T>;
 
     fn sub(self, rhs: Self) -> Self::Output {
         *self - *rhs
     }
 }
 
-impl std::ops::SubAssign<Self> for Vec2 {
+impl<T: Sub<Output = T> + Copy> std::ops::SubAssign<Self> for Vec2<T> {
     fn sub_assign(&mut self, rhs: Self) {
         *self = *self - rhs;
     }
-}
\ No newline at end of file
+}
+
+/// Iterate every integer grid cell on the straight segment from `a` to `b`,
+/// inclusive of both endpoints, using Bresenham's line algorithm.
+pub fn line(a: Vec2, b: Vec2) -> impl Iterator<Item = Vec2> {
+    let dx = (b.x - a.x).abs();
+    let dy = -(b.y - a.y).abs();
+    let step = Vec2::new((b.x - a.x).signum(), (b.y - a.y).signum());
+
+    let mut pos = Some(a);
+    let mut err = dx + dy;
+
+    std::iter::from_fn(move || {
+        let this_pos = pos?;
+
+        if this_pos == b {
+            pos = None;
+            return Some(this_pos);
+        }
+
+        let mut next = this_pos;
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            next.x += step.x;
+        }
+        if e2 <= dx {
+            err += dx;
+            next.y += step.y;
+        }
+        pos = Some(next);
+
+        Some(this_pos)
+    })
+}
+
+/// The area enclosed by the closed polygon with the given vertices, using the
+/// shoelace formula.
+///
+/// The last vertex is implicitly joined back to the first. Half units of area
+/// are rounded towards zero.
+pub fn polygon_area(vertices: &[Vec2]) -> i64 {
+    // A = 1/2 * ∑(y_i + y_(i+1)) * (x_i - x_(i+1))
+    let shifted = vertices.iter().cycle().skip(1);
+    let twice_area = vertices
+        .iter()
+        .zip(shifted)
+        .map(|(a, b)| (a.y + b.y) * (a.x - b.x))
+        .sum::<i64>();
+    twice_area.abs() / 2
+}
+
+/// As [`polygon_area`], but using the trapezoid formulation of the shoelace
+/// formula, which only needs one multiplication per vertex.
+pub fn polygon_area_trapezoid(vertices: &[Vec2]) -> i64 {
+    // A = 1/2 * ∑x_i * (y_(i+1) - y_(i-1))
+    let n = vertices.len();
+    let twice_area = (0..n)
+        .map(|i| {
+            let prev = vertices[(i + n - 1) % n];
+            let next = vertices[(i + 1) % n];
+            vertices[i].x * (next.y - prev.y)
+        })
+        .sum::<i64>();
+    twice_area.abs() / 2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diagonal() {
+        let points = line(Vec2::new(0, 0), Vec2::new(3, 4)).collect::<Vec<_>>();
+        assert_eq!(points.len(), 5);
+        assert_eq!(points.first(), Some(&Vec2::new(0, 0)));
+        assert_eq!(points.last(), Some(&Vec2::new(3, 4)));
+
+        let points = line(Vec2::new(3, 4), Vec2::new(0, 0)).collect::<Vec<_>>();
+        assert_eq!(points.len(), 5);
+        assert_eq!(points.first(), Some(&Vec2::new(3, 4)));
+        assert_eq!(points.last(), Some(&Vec2::new(0, 0)));
+    }
+
+    #[test]
+    fn test_line_vertical() {
+        let points = line(Vec2::new(2, 5), Vec2::new(2, -1)).collect::<Vec<_>>();
+        assert_eq!(points.len(), 7);
+        assert_eq!(points.first(), Some(&Vec2::new(2, 5)));
+        assert_eq!(points.last(), Some(&Vec2::new(2, -1)));
+        assert!(points.iter().all(|p| p.x == 2));
+    }
+
+    #[test]
+    fn test_vec2_i32() {
+        let a: Vec2<i32> = Vec2::new(3, -4);
+        let b: Vec2<i32> = Vec2::new(1, 2);
+
+        assert_eq!(a + b, Vec2::new(4, -2));
+        assert_eq!(a - b, Vec2::new(2, -6));
+        assert_eq!(a * 2, Vec2::new(6, -8));
+        assert_eq!(a.l1_norm(), 7);
+        assert_eq!(Vec2::<i32>::zero(), Vec2::new(0, 0));
+    }
+
+    #[test]
+    fn test_vec2_f64() {
+        let a: Vec2<f64> = Vec2::new(1.5, -2.5);
+        let b: Vec2<f64> = Vec2::new(0.5, 0.5);
+
+        assert_eq!(a + b, Vec2::new(2.0, -2.0));
+        assert_eq!(a - b, Vec2::new(1.0, -3.0));
+        assert_eq!(a * 2.0, Vec2::new(3.0, -5.0));
+        assert_eq!(a.l1_norm(), 4.0);
+        assert_eq!(Vec2::<f64>::zero(), Vec2::new(0.0, 0.0));
+    }
+
+    #[test]
+    fn test_dot() {
+        assert_eq!(Vec2::new(1, 2).dot(Vec2::new(3, 4)), 11);
+        assert_eq!(Vec2::new(2, 3).dot(Vec2::new(-3, 2)), 0);
+        assert_eq!(Vec2::new(5, 0).dot(Vec2::new(0, -7)), 0);
+    }
+
+    #[test]
+    fn test_cross() {
+        let east = Vec2::new(1, 0);
+        let north = Vec2::new(0, 1);
+
+        // Turning left (in y-up terms) gives a positive cross product, and
+        // turning right gives a negative one
+        assert!(east.cross(north) > 0);
+        assert!(north.cross(east) < 0);
+        assert_eq!(Vec2::new(2, 4).cross(Vec2::new(-1, -2)), 0);
+        assert_eq!(Vec2::new(3, 1).cross(Vec2::new(1, 2)), 5);
+    }
+
+    #[test]
+    fn test_line_single_point() {
+        let points = line(Vec2::new(1, 1), Vec2::new(1, 1)).collect::<Vec<_>>();
+        assert_eq!(points, vec![Vec2::new(1, 1)]);
+    }
+
+    #[test]
+    fn test_polygon_area() {
+        let polygons: &[&[(i64, i64)]] = &[
+            &[(0, 0), (4, 0), (4, 3), (0, 3)],
+            &[(0, 0), (0, 3), (4, 3), (4, 0)],
+            &[(0, 0), (6, 0), (6, 5), (4, 5), (4, 7), (0, 7)],
+            &[(1, 1), (5, 2), (3, 6)],
+            &[(-3, -2), (2, -4), (5, 1), (1, 5), (-4, 3)],
+            &[(0, 0), (2, 0)],
+            &[],
+        ];
+        let expected = [12, 12, 38, 9, 51, 0, 0];
+
+        for (polygon, expected) in polygons.iter().zip(expected) {
+            let vertices = polygon
+                .iter()
+                .map(|&(x, y)| Vec2::new(x, y))
+                .collect::<Vec<_>>();
+            assert_eq!(polygon_area(&vertices), expected);
+            assert_eq!(polygon_area_trapezoid(&vertices), expected);
+        }
+    }
+}